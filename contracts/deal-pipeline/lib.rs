@@ -0,0 +1,417 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::deal_pipeline::DealPipelineRef;
+
+/// Tracks the investment deals of a DAO from sourcing to exit, with the Governor
+/// proposals that funded them and what they returned, for portfolio reporting.
+#[ink::contract]
+mod deal_pipeline {
+    use ink::{
+        prelude::{
+            string::String,
+            vec::Vec,
+        },
+        storage::Mapping,
+    };
+
+    pub type DealId = u64;
+
+    /// Id of a proposal of the Governor.
+    pub type ProposalId = u64;
+
+    /// Bounds the funding proposals linked to one deal.
+    const MAX_LINKED_PROPOSALS: usize = 10;
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PipelineError {
+        Unauthorized,
+        DealNotFound,
+        /// The deal is not in a stage the call applies to.
+        InvalidStage,
+        TooManyProposals,
+        AmountShouldNotBeZero,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Stage {
+        Sourced,
+        Diligence,
+        Approved,
+        Funded,
+        Exited,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Deal {
+        /// Name of the deal, e.g. the company and the round.
+        name: String,
+        stage: Stage,
+        /// Governor proposals funding the deal.
+        proposals: Vec<ProposalId>,
+        /// Native tokens invested in the deal.
+        invested: Balance,
+        /// Native tokens the deal returned so far, distributions and exit included.
+        returned: Balance,
+        /// Last change of stage.
+        updated_at: Timestamp,
+    }
+
+    /// Totals over all deals.
+    #[derive(Debug, Clone, Default, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Portfolio {
+        /// Deals in each stage, in the order of [`Stage`].
+        deals: [u32; 5],
+        invested: Balance,
+        returned: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DealAdded {
+        #[ink(topic)]
+        deal_id: DealId,
+        name: String,
+    }
+
+    #[ink(event)]
+    pub struct StageChanged {
+        #[ink(topic)]
+        deal_id: DealId,
+        stage: Stage,
+    }
+
+    #[ink(event)]
+    pub struct ProposalLinked {
+        #[ink(topic)]
+        deal_id: DealId,
+        #[ink(topic)]
+        proposal_id: ProposalId,
+    }
+
+    #[ink(event)]
+    pub struct ReturnRecorded {
+        #[ink(topic)]
+        deal_id: DealId,
+        amount: Balance,
+    }
+
+    #[ink(storage)]
+    pub struct DealPipeline {
+        /// Account maintaining the pipeline, e.g. the Governor or an investment
+        /// committee.
+        manager: AccountId,
+        deals: Mapping<DealId, Deal>,
+        next_deal_id: DealId,
+        portfolio: Portfolio,
+    }
+
+    impl DealPipeline {
+        #[ink(constructor)]
+        pub fn new(manager: AccountId) -> Self {
+            Self {
+                manager,
+                deals: Mapping::default(),
+                next_deal_id: 0,
+                portfolio: Portfolio::default(),
+            }
+        }
+
+        #[ink(message)]
+        pub fn manager(&self) -> AccountId {
+            self.manager
+        }
+
+        /// Hands the pipeline over to another manager. Only callable by the manager.
+        #[ink(message)]
+        pub fn set_manager(&mut self, manager: AccountId) -> Result<(), PipelineError> {
+            self.ensure_manager()?;
+            self.manager = manager;
+
+            Ok(())
+        }
+
+        /// Adds a newly sourced deal. Only callable by the manager.
+        #[ink(message)]
+        pub fn add_deal(&mut self, name: String) -> Result<DealId, PipelineError> {
+            self.ensure_manager()?;
+
+            let deal_id = self.next_deal_id;
+            self.deals.insert(
+                deal_id,
+                &Deal {
+                    name: name.clone(),
+                    stage: Stage::Sourced,
+                    proposals: Vec::new(),
+                    invested: 0,
+                    returned: 0,
+                    updated_at: self.env().block_timestamp(),
+                },
+            );
+            self.next_deal_id += 1;
+            self.portfolio.deals[Stage::Sourced as usize] += 1;
+
+            self.env().emit_event(DealAdded { deal_id, name });
+
+            Ok(deal_id)
+        }
+
+        /// Moves a deal from sourced to diligence, or from diligence to approved.
+        /// Only callable by the manager.
+        #[ink(message)]
+        pub fn advance(&mut self, deal_id: DealId) -> Result<(), PipelineError> {
+            self.ensure_manager()?;
+
+            let mut deal = self.deal_of(deal_id)?;
+            let stage = match deal.stage {
+                Stage::Sourced => Stage::Diligence,
+                Stage::Diligence => Stage::Approved,
+                _ => return Err(PipelineError::InvalidStage),
+            };
+            self.set_stage(deal_id, &mut deal, stage);
+
+            Ok(())
+        }
+
+        /// Links a Governor proposal funding a deal that has not exited. Only
+        /// callable by the manager.
+        #[ink(message)]
+        pub fn link_proposal(
+            &mut self,
+            deal_id: DealId,
+            proposal_id: ProposalId,
+        ) -> Result<(), PipelineError> {
+            self.ensure_manager()?;
+
+            let mut deal = self.deal_of(deal_id)?;
+            if deal.stage == Stage::Exited {
+                return Err(PipelineError::InvalidStage)
+            }
+
+            if deal.proposals.contains(&proposal_id) {
+                return Ok(())
+            }
+
+            if deal.proposals.len() >= MAX_LINKED_PROPOSALS {
+                return Err(PipelineError::TooManyProposals)
+            }
+
+            deal.proposals.push(proposal_id);
+            self.deals.insert(deal_id, &deal);
+
+            self.env().emit_event(ProposalLinked {
+                deal_id,
+                proposal_id,
+            });
+
+            Ok(())
+        }
+
+        /// Records `amount` invested in an approved deal, which is then funded, or a
+        /// follow-on investment in a funded one. Only callable by the manager.
+        #[ink(message)]
+        pub fn record_investment(
+            &mut self,
+            deal_id: DealId,
+            amount: Balance,
+        ) -> Result<(), PipelineError> {
+            self.ensure_manager()?;
+
+            if amount == 0 {
+                return Err(PipelineError::AmountShouldNotBeZero)
+            }
+
+            let mut deal = self.deal_of(deal_id)?;
+            if !matches!(deal.stage, Stage::Approved | Stage::Funded) {
+                return Err(PipelineError::InvalidStage)
+            }
+
+            deal.invested = deal.invested.saturating_add(amount);
+            self.portfolio.invested = self.portfolio.invested.saturating_add(amount);
+            if deal.stage == Stage::Approved {
+                self.set_stage(deal_id, &mut deal, Stage::Funded);
+            } else {
+                self.deals.insert(deal_id, &deal);
+            }
+
+            Ok(())
+        }
+
+        /// Records `amount` returned by a funded deal, e.g. a distribution. With
+        /// `exited`, the amount is the exit proceeds and the deal is exited. Only
+        /// callable by the manager.
+        #[ink(message)]
+        pub fn record_return(
+            &mut self,
+            deal_id: DealId,
+            amount: Balance,
+            exited: bool,
+        ) -> Result<(), PipelineError> {
+            self.ensure_manager()?;
+
+            let mut deal = self.deal_of(deal_id)?;
+            if deal.stage != Stage::Funded {
+                return Err(PipelineError::InvalidStage)
+            }
+
+            deal.returned = deal.returned.saturating_add(amount);
+            self.portfolio.returned = self.portfolio.returned.saturating_add(amount);
+            if exited {
+                self.set_stage(deal_id, &mut deal, Stage::Exited);
+            } else {
+                self.deals.insert(deal_id, &deal);
+            }
+
+            self.env().emit_event(ReturnRecorded { deal_id, amount });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn deal(&self, deal_id: DealId) -> Option<Deal> {
+            self.deals.get(deal_id)
+        }
+
+        #[ink(message)]
+        pub fn deal_count(&self) -> u64 {
+            self.next_deal_id
+        }
+
+        /// Returns up to `limit` deals in `stage`, among the deals from `offset` on,
+        /// with their ids.
+        #[ink(message)]
+        pub fn deals_in_stage(
+            &self,
+            stage: Stage,
+            offset: DealId,
+            limit: u32,
+        ) -> Vec<(DealId, Deal)> {
+            (offset..self.next_deal_id)
+                .filter_map(|deal_id| self.deals.get(deal_id).map(|deal| (deal_id, deal)))
+                .filter(|(_, deal)| deal.stage == stage)
+                .take(limit as usize)
+                .collect()
+        }
+
+        /// Returns the deals per stage and the totals invested and returned.
+        #[ink(message)]
+        pub fn portfolio(&self) -> Portfolio {
+            self.portfolio.clone()
+        }
+
+        fn set_stage(&mut self, deal_id: DealId, deal: &mut Deal, stage: Stage) {
+            self.portfolio.deals[deal.stage as usize] -= 1;
+            self.portfolio.deals[stage as usize] += 1;
+            deal.stage = stage;
+            deal.updated_at = self.env().block_timestamp();
+            self.deals.insert(deal_id, deal);
+
+            self.env().emit_event(StageChanged { deal_id, stage });
+        }
+
+        fn deal_of(&self, deal_id: DealId) -> Result<Deal, PipelineError> {
+            match self.deals.get(deal_id) {
+                Some(deal) => Ok(deal),
+                None => Err(PipelineError::DealNotFound),
+            }
+        }
+
+        fn ensure_manager(&self) -> Result<(), PipelineError> {
+            if self.env().caller() != self.manager {
+                return Err(PipelineError::Unauthorized)
+            }
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn default_accounts() -> ink::env::test::DefaultAccounts<Environment> {
+            ink::env::test::default_accounts::<Environment>()
+        }
+
+        #[ink::test]
+        fn deals_move_through_the_stages() {
+            let accounts = default_accounts();
+            let mut pipeline = DealPipeline::new(accounts.alice);
+
+            ink::env::test::set_caller::<Environment>(accounts.bob);
+            assert_eq!(
+                pipeline.add_deal(String::from("Acme seed")),
+                Err(PipelineError::Unauthorized)
+            );
+
+            ink::env::test::set_caller::<Environment>(accounts.alice);
+            assert_eq!(pipeline.add_deal(String::from("Acme seed")), Ok(0));
+            assert_eq!(
+                pipeline.record_investment(0, 100),
+                Err(PipelineError::InvalidStage)
+            );
+            assert_eq!(pipeline.advance(0), Ok(()));
+            assert_eq!(pipeline.advance(0), Ok(()));
+            assert_eq!(pipeline.advance(0), Err(PipelineError::InvalidStage));
+
+            assert_eq!(pipeline.link_proposal(0, 7), Ok(()));
+            assert_eq!(pipeline.link_proposal(0, 7), Ok(()));
+            assert_eq!(pipeline.record_investment(0, 100), Ok(()));
+            assert_eq!(pipeline.record_investment(0, 50), Ok(()));
+            assert_eq!(pipeline.record_return(0, 30, false), Ok(()));
+            assert_eq!(pipeline.record_return(0, 270, true), Ok(()));
+            assert_eq!(
+                pipeline.record_return(0, 10, false),
+                Err(PipelineError::InvalidStage)
+            );
+            assert_eq!(
+                pipeline.link_proposal(0, 8),
+                Err(PipelineError::InvalidStage)
+            );
+
+            let deal = pipeline.deal(0).unwrap();
+            assert_eq!(deal.stage, Stage::Exited);
+            assert_eq!(deal.proposals, Vec::from([7]));
+            assert_eq!(deal.invested, 150);
+            assert_eq!(deal.returned, 300);
+        }
+
+        #[ink::test]
+        fn portfolio_counts_deals_per_stage() {
+            let accounts = default_accounts();
+            let mut pipeline = DealPipeline::new(accounts.alice);
+
+            for name in ["Acme seed", "Globex series A", "Initech bridge"] {
+                pipeline.add_deal(String::from(name)).unwrap();
+            }
+            pipeline.advance(1).unwrap();
+            pipeline.advance(2).unwrap();
+            pipeline.advance(2).unwrap();
+            pipeline.record_investment(2, 500).unwrap();
+
+            assert_eq!(
+                pipeline.portfolio(),
+                Portfolio {
+                    deals: [1, 1, 0, 1, 0],
+                    invested: 500,
+                    returned: 0,
+                }
+            );
+            let funded = pipeline.deals_in_stage(Stage::Funded, 0, 10);
+            assert_eq!(funded.len(), 1);
+            assert_eq!(funded[0].0, 2);
+            assert_eq!(pipeline.deals_in_stage(Stage::Sourced, 1, 10).len(), 0);
+        }
+    }
+}
@@ -3,12 +3,203 @@
 
 pub use self::governance_token::GovernanceTokenRef;
 
-#[openbrush::implementation(PSP22, PSP22Metadata)]
+/// Binary searches `count` checkpoints ordered by timestamp and returns how many of
+/// them were written at or before `timestamp`.
+pub fn checkpoints_until<F>(count: u32, timestamp: u64, timestamp_of: F) -> u32
+where
+    F: Fn(u32) -> u64,
+{
+    let mut low = 0;
+    let mut high = count;
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+
+        if timestamp_of(middle) > timestamp {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    low
+}
+
+#[openbrush::implementation(PSP22, PSP22Metadata, Ownable, AccessControl)]
 #[openbrush::contract]
 mod governance_token {
 
-    use ink::storage::Mapping;
-    use openbrush::traits::Storage;
+    use crate::checkpoints_until;
+    use ink::{
+        env::hash::Blake2x256,
+        prelude::vec::Vec,
+        storage::Mapping,
+    };
+    use openbrush::{
+        contracts::{
+            ownable::OwnableError,
+            psp22::PSP22Error,
+        },
+        modifiers,
+        traits::Storage,
+    };
+
+    /// Voting weight of the whole supply, i.e. weights are in basis points.
+    pub const BASIS_POINTS: u64 = 10_000;
+
+    /// Role allowed to freeze accounts and manage the transfer whitelist next to the
+    /// owner.
+    pub const COMPLIANCE: RoleType = ink::selector_id!("COMPLIANCE");
+
+    /// Most epochs a single `emit_rewards` call mints, so that its cost stays bounded
+    /// after a long pause; later calls catch up on the rest.
+    pub const MAX_EPOCHS_PER_EMISSION: u64 = 52;
+
+    /// Most holders one page of `holders` returns.
+    pub const MAX_PAGE_SIZE: u32 = 100;
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum TokenError {
+        InsufficientReserve,
+        CapExceeded,
+        PermitExpired,
+        PermitInvalidSignature,
+        Unauthorized,
+        InvalidEmissionSchedule,
+        NoEmissionSchedule,
+        PSP22(PSP22Error),
+        Ownable(OwnableError),
+    }
+
+    impl From<PSP22Error> for TokenError {
+        fn from(error: PSP22Error) -> Self {
+            TokenError::PSP22(error)
+        }
+    }
+
+    impl From<OwnableError> for TokenError {
+        fn from(error: OwnableError) -> Self {
+            TokenError::Ownable(error)
+        }
+    }
+
+    /// Emitted when tokens leave the undistributed reserve.
+    #[ink(event)]
+    pub struct TokensDistributed {
+        #[ink(topic)]
+        recipient: AccountId,
+        amount: Balance,
+    }
+
+    /// Standard PSP22 transfer event.
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    /// Standard PSP22 approval event.
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DelegateChanged {
+        #[ink(topic)]
+        delegator: AccountId,
+        from_delegate: Option<AccountId>,
+        to_delegate: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DelegateVotesChanged {
+        #[ink(topic)]
+        delegate: AccountId,
+        previous_votes: Balance,
+        new_votes: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AccountFreezeChanged {
+        #[ink(topic)]
+        account: AccountId,
+        frozen: bool,
+    }
+
+    #[ink(event)]
+    pub struct WhitelistChanged {
+        #[ink(topic)]
+        account: AccountId,
+        whitelisted: bool,
+    }
+
+    #[ink(event)]
+    pub struct TransferRestrictionChanged {
+        restricted: bool,
+    }
+
+    /// Delegated voting power of an account from `timestamp` on.
+    #[derive(
+        Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Checkpoint {
+        timestamp: Timestamp,
+        votes: Balance,
+    }
+
+    /// Balance of an account from `timestamp` on.
+    #[derive(
+        Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct BalanceCheckpoint {
+        timestamp: Timestamp,
+        balance: Balance,
+    }
+
+    /// Total and circulating supply from `timestamp` on.
+    #[derive(
+        Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SupplyCheckpoint {
+        timestamp: Timestamp,
+        total_supply: Balance,
+        circulating_supply: Balance,
+    }
+
+    /// Rewards minted every `epoch_length` milliseconds into `rewards_pool`, starting
+    /// at `initial_emission` and shrinking by `decay` basis points per epoch.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EmissionSchedule {
+        rewards_pool: AccountId,
+        initial_emission: Balance,
+        decay: u64,
+        epoch_length: Timestamp,
+    }
 
     #[ink(storage)]
     #[derive(Default, Storage)]
@@ -19,11 +210,147 @@ mod governance_token {
         #[storage_field]
         metadata: metadata::Data,
 
-        balances: Mapping<AccountId, Balance>,
+        #[storage_field]
+        ownable: ownable::Data,
 
-        total_supply: Balance,
+        #[storage_field]
+        access: access_control::Data,
 
-        circulating_supply: Balance,
+        /// Maximum total supply, enforced on every mint.
+        cap: Option<Balance>,
+
+        /// Per-owner counter making every permit signature single use.
+        nonces: Mapping<AccountId, u64>,
+
+        delegates: Mapping<AccountId, AccountId>,
+
+        /// Append-only history of the delegated votes of every delegatee.
+        checkpoints: Mapping<(AccountId, u32), Checkpoint>,
+
+        checkpoint_count: Mapping<AccountId, u32>,
+
+        supply_checkpoints: Mapping<u32, SupplyCheckpoint>,
+
+        supply_checkpoint_count: u32,
+
+        /// Append-only history of the balance of every account, so that holders who
+        /// delegated can still vote directly with their balance at a snapshot.
+        balance_checkpoints: Mapping<(AccountId, u32), BalanceCheckpoint>,
+
+        balance_checkpoint_count: Mapping<AccountId, u32>,
+
+        /// Link to off-chain branding and token documentation.
+        metadata_uri: Option<String>,
+
+        /// Accounts with a nonzero balance, excluding the undistributed reserve.
+        holders: Mapping<u32, AccountId>,
+
+        holder_index: Mapping<AccountId, u32>,
+
+        holder_count: u32,
+
+        /// Accounts that can neither send nor receive tokens.
+        frozen: Mapping<AccountId, ()>,
+
+        /// Whether only whitelisted accounts can send and receive tokens.
+        transfers_restricted: bool,
+
+        whitelist: Mapping<AccountId, ()>,
+
+        emission_schedule: Option<EmissionSchedule>,
+
+        emission_start: Timestamp,
+
+        emitted_epochs: u64,
+
+        /// Emission of the next epoch not minted yet.
+        next_emission: Balance,
+    }
+
+    #[overrider(psp22::Internal)]
+    fn _emit_transfer_event(
+        &self,
+        from: Option<AccountId>,
+        to: Option<AccountId>,
+        amount: Balance,
+    ) {
+        self.env().emit_event(Transfer {
+            from,
+            to,
+            value: amount,
+        });
+    }
+
+    #[overrider(psp22::Internal)]
+    fn _emit_approval_event(
+        &self,
+        owner: AccountId,
+        spender: AccountId,
+        amount: Balance,
+    ) {
+        self.env().emit_event(Approval {
+            owner,
+            spender,
+            value: amount,
+        });
+    }
+
+    #[overrider(psp22::Internal)]
+    fn _before_token_transfer(
+        &mut self,
+        from: Option<&AccountId>,
+        to: Option<&AccountId>,
+        _amount: &Balance,
+    ) -> Result<(), PSP22Error> {
+        if from.map_or(false, |account| self.frozen.contains(account))
+            || to.map_or(false, |account| self.frozen.contains(account))
+        {
+            return Err(PSP22Error::Custom(String::from("AccountFrozen")))
+        }
+
+        // Burns stay open, and the reserve distributes to whitelisted accounts only
+        if self.transfers_restricted {
+            if let Some(to) = to {
+                let reserve = self.env().account_id();
+                if from.map_or(false, |account| {
+                    *account != reserve && !self.whitelist.contains(account)
+                }) {
+                    return Err(PSP22Error::Custom(String::from("SenderNotWhitelisted")))
+                }
+                if !self.whitelist.contains(to) {
+                    return Err(PSP22Error::Custom(String::from(
+                        "RecipientNotWhitelisted",
+                    )))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Keeps delegated votes in sync with every transfer, mint and burn
+    #[overrider(psp22::Internal)]
+    fn _after_token_transfer(
+        &mut self,
+        from: Option<&AccountId>,
+        to: Option<&AccountId>,
+        amount: &Balance,
+    ) -> Result<(), PSP22Error> {
+        let from_delegate = from.and_then(|account| self.delegates.get(account));
+        let to_delegate = to.and_then(|account| self.delegates.get(account));
+
+        self.move_voting_power(from_delegate, to_delegate, *amount);
+
+        if let Some(from) = from {
+            self.update_holder(*from);
+            self.checkpoint_balance(*from);
+        }
+        if let Some(to) = to {
+            self.update_holder(*to);
+            self.checkpoint_balance(*to);
+        }
+
+        Ok(())
     }
 
     impl GovernanceToken {
@@ -33,12 +360,25 @@ mod governance_token {
             name: Option<String>,
             symbol: Option<String>,
             decimal: u8,
+            cap: Option<Balance>,
         ) -> Self {
             let mut _instance = Self::default();
 
+            if let Some(cap) = cap {
+                assert!(initial_supply <= cap, "Initial supply exceeds cap");
+            }
+            _instance.cap = cap;
+
+            ownable::Internal::_init_with_owner(&mut _instance, Self::env().caller());
+            access_control::Internal::_init_with_admin(
+                &mut _instance,
+                Some(Self::env().caller()),
+            );
+
+            // The supply is held by the token contract until dropped to voters
             psp22::Internal::_mint_to(
                 &mut _instance,
-                Self::env().caller(),
+                Self::env().account_id(),
                 initial_supply,
             )
             .expect("Should mint");
@@ -47,84 +387,1343 @@ mod governance_token {
             _instance.metadata.symbol.set(&symbol);
             _instance.metadata.decimals.set(&decimal);
 
-            _instance.balances = Mapping::default();
-            _instance.total_supply = initial_supply;
-            _instance.circulating_supply = 0;
+            _instance.checkpoint_supply();
 
             _instance
         }
 
-        // A way to drop some tokens to users for voting
+        // A way to drop some tokens to users for voting. Only the owner, usually the
+        // DAO, can distribute the reserve
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn transfer_to(
+            &mut self,
+            recipient: AccountId,
+            amount: Balance,
+        ) -> Result<(), TokenError> {
+            if self.reserve() < amount {
+                return Err(TokenError::InsufficientReserve)
+            }
+
+            let reserve = self.env().account_id();
+            psp22::Internal::_transfer_from_to(
+                self,
+                reserve,
+                recipient,
+                amount,
+                Vec::new(),
+            )?;
+            self.checkpoint_supply();
+
+            self.env()
+                .emit_event(TokensDistributed { recipient, amount });
+
+            Ok(())
+        }
+
+        /// Mints new tokens straight into circulation. Only the owner, usually the DAO
+        /// acting through a proposal, can expand the supply.
         #[ink(message)]
-        pub fn transfer_to(&mut self, recipient: AccountId, amount: Balance) {
-            if amount + self.circulating_supply < self.total_supply {
-                let recipient_balance = self.balance_of(recipient);
+        #[modifiers(only_owner)]
+        pub fn mint(&mut self, to: AccountId, amount: Balance) -> Result<(), TokenError> {
+            self.mint_tokens(to, amount)
+        }
 
-                self.balances
-                    .insert(recipient, &(recipient_balance + amount));
-                self.circulating_supply += amount;
+        /// Replaces the emission schedule, restarting the epochs from now. Only the
+        /// owner, usually the DAO acting through a proposal, can change it.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_emission_schedule(
+            &mut self,
+            schedule: Option<EmissionSchedule>,
+        ) -> Result<(), TokenError> {
+            if let Some(schedule) = schedule {
+                if schedule.epoch_length == 0 || schedule.decay > BASIS_POINTS {
+                    return Err(TokenError::InvalidEmissionSchedule)
+                }
             }
+
+            self.emission_schedule = schedule;
+            self.emission_start = self.env().block_timestamp();
+            self.emitted_epochs = 0;
+            self.next_emission = schedule.map_or(0, |schedule| schedule.initial_emission);
+
+            Ok(())
         }
 
         #[ink(message)]
-        pub fn weight(&self, account: AccountId) -> u64 {
-            let balance = self.balances.get(account).unwrap_or_default();
-            (balance * 100 / self.total_supply) as u64
+        pub fn emission_schedule(&self) -> Option<EmissionSchedule> {
+            self.emission_schedule
         }
 
+        /// Mints the emissions of all elapsed epochs into the rewards pool. Anyone can
+        /// trigger it; returns the minted amount.
         #[ink(message)]
-        pub fn balance_of(&self, account: AccountId) -> Balance {
-            self.balances.get(account).unwrap_or_default()
+        pub fn emit_rewards(&mut self) -> Result<Balance, TokenError> {
+            let schedule = match self.emission_schedule {
+                Some(value) => value,
+                None => return Err(TokenError::NoEmissionSchedule),
+            };
+
+            let elapsed_epochs = (self.env().block_timestamp() - self.emission_start)
+                / schedule.epoch_length;
+            let epochs = elapsed_epochs
+                .saturating_sub(self.emitted_epochs)
+                .min(MAX_EPOCHS_PER_EMISSION);
+
+            let retained = (BASIS_POINTS - schedule.decay) as Balance;
+            let mut emission = self.next_emission;
+            let mut amount: Balance = 0;
+            for _ in 0..epochs {
+                amount = amount.saturating_add(emission);
+                emission = emission.saturating_mul(retained) / BASIS_POINTS as Balance;
+            }
+
+            // Emissions stop at the cap instead of failing
+            if let Some(cap) = self.cap {
+                amount =
+                    amount.min(cap.saturating_sub(psp22::Internal::_total_supply(self)));
+            }
+
+            self.emitted_epochs += epochs;
+            self.next_emission = emission;
+            if amount > 0 {
+                self.mint_tokens(schedule.rewards_pool, amount)?;
+            }
+
+            Ok(amount)
         }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_name(&mut self, name: Option<String>) -> Result<(), TokenError> {
+            self.metadata.name.set(&name);
+            Ok(())
+        }
 
-        fn default_accounts() -> ink::env::test::DefaultAccounts<Environment> {
-            ink::env::test::default_accounts::<Environment>()
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_symbol(&mut self, symbol: Option<String>) -> Result<(), TokenError> {
+            self.metadata.symbol.set(&symbol);
+            Ok(())
         }
 
-        fn alice() -> AccountId {
-            default_accounts().alice
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_metadata_uri(
+            &mut self,
+            metadata_uri: Option<String>,
+        ) -> Result<(), TokenError> {
+            self.metadata_uri = metadata_uri;
+            Ok(())
         }
 
-        #[ink::test]
-        fn new_works() {
-            let contract = GovernanceToken::new(
-                1000,
-                Some("VoteCoin".into()),
-                Some("VCT".into()),
-                8,
-            );
-            assert_eq!(contract.total_supply, 1000);
-            assert_eq!(contract.circulating_supply, 0);
+        #[ink(message)]
+        pub fn metadata_uri(&self) -> Option<String> {
+            self.metadata_uri.clone()
         }
 
-        #[ink::test]
-        fn transfer_to_works() {
-            let mut contract = GovernanceToken::new(
-                1000,
-                Some("VoteCoin".into()),
-                Some("VCT".into()),
-                8,
+        #[ink(message)]
+        pub fn cap(&self) -> Option<Balance> {
+            self.cap
+        }
+
+        /// Destroys `amount` of the caller's tokens.
+        #[ink(message)]
+        pub fn burn(&mut self, amount: Balance) -> Result<(), TokenError> {
+            let caller = self.env().caller();
+            self.burn_tokens(caller, amount)
+        }
+
+        /// Destroys `amount` of `account`'s tokens, spending the caller's allowance.
+        #[ink(message)]
+        pub fn burn_from(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), TokenError> {
+            let caller = self.env().caller();
+            let allowance = psp22::Internal::_allowance(self, &account, &caller);
+
+            if allowance < amount {
+                return Err(PSP22Error::InsufficientAllowance.into())
+            }
+
+            psp22::Internal::_approve_from_to(self, account, caller, allowance - amount)?;
+            self.burn_tokens(account, amount)
+        }
+
+        /// Transfers tokens from the caller to every recipient in `transfers`. Either all
+        /// transfers succeed or none is applied.
+        #[ink(message)]
+        pub fn transfer_batch(
+            &mut self,
+            transfers: Vec<(AccountId, Balance)>,
+        ) -> Result<(), TokenError> {
+            let caller = self.env().caller();
+            let total = match transfers
+                .iter()
+                .try_fold(0 as Balance, |total, (_, amount)| {
+                    total.checked_add(*amount)
+                }) {
+                Some(value) => value,
+                None => return Err(PSP22Error::InsufficientBalance.into()),
+            };
+
+            if psp22::Internal::_balance_of(self, &caller) < total {
+                return Err(PSP22Error::InsufficientBalance.into())
+            }
+
+            for (to, value) in transfers {
+                psp22::Internal::_transfer_from_to(self, caller, to, value, Vec::new())?;
+            }
+
+            Ok(())
+        }
+
+        /// Approves `spender` on behalf of `owner` using an ECDSA signature by `owner`
+        /// over [`GovernanceToken::permit_digest`], so that no prior `approve` call is
+        /// needed.
+        #[ink(message)]
+        pub fn permit(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            signature: [u8; 65],
+        ) -> Result<(), TokenError> {
+            if self.env().block_timestamp() > deadline {
+                return Err(TokenError::PermitExpired)
+            }
+
+            let digest = self.permit_digest(owner, spender, value, deadline);
+            let mut public_key = [0u8; 33];
+            if ink::env::ecdsa_recover(&signature, &digest, &mut public_key).is_err() {
+                return Err(TokenError::PermitInvalidSignature)
+            }
+
+            let mut signer = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&public_key, &mut signer);
+            if AccountId::from(signer) != owner {
+                return Err(TokenError::PermitInvalidSignature)
+            }
+
+            self.nonces.insert(owner, &(self.nonces(owner) + 1));
+            psp22::Internal::_approve_from_to(self, owner, spender, value)?;
+
+            Ok(())
+        }
+
+        /// Returns the hash `owner` has to sign to permit `spender` to spend `value`.
+        #[ink(message)]
+        pub fn permit_digest(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+        ) -> [u8; 32] {
+            let mut digest = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(
+                &(
+                    self.env().account_id(),
+                    owner,
+                    spender,
+                    value,
+                    self.nonces(owner),
+                    deadline,
+                ),
+                &mut digest,
             );
-            assert_eq!(contract.total_supply, 1000);
+            digest
+        }
 
-            contract.transfer_to(alice(), 10);
-            assert_eq!(contract.circulating_supply, 10);
-            assert_eq!(contract.balance_of(alice()), 10);
+        #[ink(message)]
+        pub fn nonces(&self, owner: AccountId) -> u64 {
+            self.nonces.get(owner).unwrap_or_default()
         }
 
-        #[ink::test]
-        fn weight_works() {
-            let mut contract =
-                GovernanceToken::new(100, Some("VoteCoin".into()), Some("VCT".into()), 8);
-            assert_eq!(contract.total_supply, 100);
+        /// Delegates the caller's voting power, including future balance changes, to
+        /// `delegatee`. Holders have to delegate to themselves to vote directly.
+        #[ink(message)]
+        pub fn delegate(&mut self, delegatee: AccountId) -> Result<(), TokenError> {
+            let caller = self.env().caller();
+            let previous = self.delegates.get(caller);
+
+            self.delegates.insert(caller, &delegatee);
+            self.env().emit_event(DelegateChanged {
+                delegator: caller,
+                from_delegate: previous,
+                to_delegate: delegatee,
+            });
+
+            let balance = psp22::Internal::_balance_of(self, &caller);
+            self.move_voting_power(previous, Some(delegatee), balance);
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn delegates(&self, account: AccountId) -> Option<AccountId> {
+            self.delegates.get(account)
+        }
+
+        /// Returns the voting power currently delegated to `account`.
+        #[ink(message)]
+        pub fn get_votes(&self, account: AccountId) -> Balance {
+            match self.checkpoint_count.get(account).unwrap_or_default() {
+                0 => 0,
+                count => {
+                    self.checkpoints
+                        .get((account, count - 1))
+                        .unwrap_or_default()
+                        .votes
+                }
+            }
+        }
+
+        /// Returns the voting power delegated to `account` at `timestamp`.
+        #[ink(message)]
+        pub fn get_past_votes(
+            &self,
+            account: AccountId,
+            timestamp: Timestamp,
+        ) -> Balance {
+            let count = self.checkpoint_count.get(account).unwrap_or_default();
+            let position = checkpoints_until(count, timestamp, |index| {
+                self.checkpoints
+                    .get((account, index))
+                    .unwrap_or_default()
+                    .timestamp
+            });
+
+            match position {
+                0 => 0,
+                index => {
+                    self.checkpoints
+                        .get((account, index - 1))
+                        .unwrap_or_default()
+                        .votes
+                }
+            }
+        }
+
+        /// Returns the balance of `account` at `timestamp`.
+        #[ink(message)]
+        pub fn balance_of_at(&self, account: AccountId, timestamp: Timestamp) -> Balance {
+            let count = self
+                .balance_checkpoint_count
+                .get(account)
+                .unwrap_or_default();
+            let position = checkpoints_until(count, timestamp, |index| {
+                self.balance_checkpoints
+                    .get((account, index))
+                    .unwrap_or_default()
+                    .timestamp
+            });
+
+            match position {
+                0 => 0,
+                index => {
+                    self.balance_checkpoints
+                        .get((account, index - 1))
+                        .unwrap_or_default()
+                        .balance
+                }
+            }
+        }
+
+        /// Freezes or unfreezes `account`. Callable by the owner or a holder of the
+        /// [`COMPLIANCE`] role.
+        #[ink(message)]
+        pub fn set_frozen(
+            &mut self,
+            account: AccountId,
+            frozen: bool,
+        ) -> Result<(), TokenError> {
+            self.ensure_compliance()?;
+
+            if frozen {
+                self.frozen.insert(account, &());
+            } else {
+                self.frozen.remove(account);
+            }
+
+            self.env()
+                .emit_event(AccountFreezeChanged { account, frozen });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_frozen(&self, account: AccountId) -> bool {
+            self.frozen.contains(account)
+        }
+
+        /// Restricts transfers to whitelisted senders and recipients, or lifts the
+        /// restriction. Mints have to go to whitelisted accounts too, burns are always
+        /// allowed. Callable by the owner or a holder of the [`COMPLIANCE`] role.
+        #[ink(message)]
+        pub fn set_transfers_restricted(
+            &mut self,
+            restricted: bool,
+        ) -> Result<(), TokenError> {
+            self.ensure_compliance()?;
+
+            self.transfers_restricted = restricted;
+            self.env()
+                .emit_event(TransferRestrictionChanged { restricted });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn transfers_restricted(&self) -> bool {
+            self.transfers_restricted
+        }
+
+        /// Adds `account` to the transfer whitelist or removes it. Callable by the
+        /// owner or a holder of the [`COMPLIANCE`] role.
+        #[ink(message)]
+        pub fn set_whitelisted(
+            &mut self,
+            account: AccountId,
+            whitelisted: bool,
+        ) -> Result<(), TokenError> {
+            self.ensure_compliance()?;
+
+            if whitelisted {
+                self.whitelist.insert(account, &());
+            } else {
+                self.whitelist.remove(account);
+            }
+
+            self.env().emit_event(WhitelistChanged {
+                account,
+                whitelisted,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_whitelisted(&self, account: AccountId) -> bool {
+            self.whitelist.contains(account)
+        }
+
+        #[ink(message)]
+        pub fn holder_count(&self) -> u32 {
+            self.holder_count
+        }
+
+        /// Returns up to `limit` holders starting at `offset`, at most `MAX_PAGE_SIZE`.
+        /// The order changes when holders leave.
+        #[ink(message)]
+        pub fn holders(&self, offset: u32, limit: u32) -> Vec<AccountId> {
+            let end = offset
+                .saturating_add(limit.min(MAX_PAGE_SIZE))
+                .min(self.holder_count);
+
+            (offset..end)
+                .filter_map(|index| self.holders.get(index))
+                .collect()
+        }
+
+        /// Returns the total supply at `timestamp`.
+        #[ink(message)]
+        pub fn total_supply_at(&self, timestamp: Timestamp) -> Balance {
+            self.supply_at(timestamp).total_supply
+        }
+
+        /// Returns the supply held outside of the undistributed reserve.
+        #[ink(message)]
+        pub fn circulating_supply(&self) -> Balance {
+            psp22::Internal::_total_supply(self).saturating_sub(self.reserve())
+        }
+
+        /// Returns the circulating supply at `timestamp`.
+        #[ink(message)]
+        pub fn circulating_supply_at(&self, timestamp: Timestamp) -> Balance {
+            self.supply_at(timestamp).circulating_supply
+        }
+
+        /// Returns the share of the circulating supply held by `account`, in basis
+        /// points. The undistributed reserve cannot vote, so it does not count.
+        #[ink(message)]
+        pub fn weight(&self, account: AccountId) -> u64 {
+            let circulating_supply = self.circulating_supply();
+            if circulating_supply == 0 {
+                return 0
+            }
+
+            let balance = psp22::Internal::_balance_of(self, &account);
+            (balance.saturating_mul(BASIS_POINTS as Balance) / circulating_supply) as u64
+        }
+
+        /// Returns the share of the circulating supply delegated to `account`, in basis
+        /// points.
+        #[ink(message)]
+        pub fn vote_weight(&self, account: AccountId) -> u64 {
+            let circulating_supply = self.circulating_supply();
+            if circulating_supply == 0 {
+                return 0
+            }
+
+            (self
+                .get_votes(account)
+                .saturating_mul(BASIS_POINTS as Balance)
+                / circulating_supply) as u64
+        }
+
+        /// Converts an amount of whole tokens, i.e. ignoring the decimals, into a
+        /// weight in basis points of the circulating supply. Handy to derive a quorum
+        /// from a token amount.
+        #[ink(message)]
+        pub fn tokens_to_weight(&self, tokens: Balance) -> u64 {
+            let circulating_supply = self.circulating_supply();
+            if circulating_supply == 0 {
+                return 0
+            }
+
+            (tokens
+                .saturating_mul(self.token_unit())
+                .saturating_mul(BASIS_POINTS as Balance)
+                / circulating_supply) as u64
+        }
+
+        /// Converts a weight in basis points of the circulating supply into whole
+        /// tokens.
+        #[ink(message)]
+        pub fn weight_to_tokens(&self, weight: u64) -> Balance {
+            self.circulating_supply().saturating_mul(weight as Balance)
+                / BASIS_POINTS as Balance
+                / self.token_unit()
+        }
+
+        /// Returns the undistributed tokens held by the token contract itself.
+        fn reserve(&self) -> Balance {
+            psp22::Internal::_balance_of(self, &self.env().account_id())
+        }
+
+        /// Fails unless the caller is the owner or holds the [`COMPLIANCE`] role.
+        fn ensure_compliance(&self) -> Result<(), TokenError> {
+            let caller = self.env().caller();
+            if ownable::Ownable::owner(self) != Some(caller)
+                && !access_control::AccessControl::has_role(
+                    self,
+                    COMPLIANCE,
+                    Some(caller),
+                )
+            {
+                return Err(TokenError::Unauthorized)
+            }
+
+            Ok(())
+        }
+
+        /// Returns the amount of smallest units making up one whole token.
+        fn token_unit(&self) -> Balance {
+            let decimals = metadata::PSP22Metadata::token_decimals(self);
+            (10 as Balance).saturating_pow(decimals as u32)
+        }
+
+        fn burn_tokens(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), TokenError> {
+            psp22::Internal::_burn_from(self, account, amount)?;
+            self.checkpoint_supply();
+
+            Ok(())
+        }
+
+        fn move_voting_power(
+            &mut self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            amount: Balance,
+        ) {
+            if from == to || amount == 0 {
+                return
+            }
+
+            if let Some(from) = from {
+                let votes = self.get_votes(from).saturating_sub(amount);
+                self.write_checkpoint(from, votes);
+            }
+
+            if let Some(to) = to {
+                let votes = self.get_votes(to).saturating_add(amount);
+                self.write_checkpoint(to, votes);
+            }
+        }
+
+        fn mint_tokens(
+            &mut self,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), TokenError> {
+            if let Some(cap) = self.cap {
+                if psp22::Internal::_total_supply(self).saturating_add(amount) > cap {
+                    return Err(TokenError::CapExceeded)
+                }
+            }
+
+            psp22::Internal::_mint_to(self, to, amount)?;
+            self.checkpoint_supply();
+
+            Ok(())
+        }
+
+        fn update_holder(&mut self, account: AccountId) {
+            if account == self.env().account_id() {
+                return
+            }
+
+            let balance = psp22::Internal::_balance_of(self, &account);
+            match self.holder_index.get(account) {
+                None if balance > 0 => {
+                    self.holders.insert(self.holder_count, &account);
+                    self.holder_index.insert(account, &self.holder_count);
+                    self.holder_count += 1;
+                }
+                Some(index) if balance == 0 => {
+                    // Swap the last holder into the freed slot
+                    let last = self.holder_count - 1;
+                    if index != last {
+                        if let Some(moved) = self.holders.get(last) {
+                            self.holders.insert(index, &moved);
+                            self.holder_index.insert(moved, &index);
+                        }
+                    }
+                    self.holders.remove(last);
+                    self.holder_index.remove(account);
+                    self.holder_count = last;
+                }
+                _ => {}
+            }
+        }
+
+        fn checkpoint_supply(&mut self) {
+            let checkpoint = SupplyCheckpoint {
+                timestamp: self.env().block_timestamp(),
+                total_supply: psp22::Internal::_total_supply(self),
+                circulating_supply: self.circulating_supply(),
+            };
+            let count = self.supply_checkpoint_count;
+
+            // Several changes within one block collapse into a single checkpoint
+            if count > 0 {
+                if let Some(last) = self.supply_checkpoints.get(count - 1) {
+                    if last.timestamp == checkpoint.timestamp {
+                        self.supply_checkpoints.insert(count - 1, &checkpoint);
+                        return
+                    }
+                }
+            }
+
+            self.supply_checkpoints.insert(count, &checkpoint);
+            self.supply_checkpoint_count = count + 1;
+        }
+
+        fn checkpoint_balance(&mut self, account: AccountId) {
+            let checkpoint = BalanceCheckpoint {
+                timestamp: self.env().block_timestamp(),
+                balance: psp22::Internal::_balance_of(self, &account),
+            };
+            let count = self
+                .balance_checkpoint_count
+                .get(account)
+                .unwrap_or_default();
+
+            // Several changes within one block collapse into a single checkpoint
+            if count > 0 {
+                if let Some(last) = self.balance_checkpoints.get((account, count - 1)) {
+                    if last.timestamp == checkpoint.timestamp {
+                        self.balance_checkpoints
+                            .insert((account, count - 1), &checkpoint);
+                        return
+                    }
+                }
+            }
+
+            self.balance_checkpoints
+                .insert((account, count), &checkpoint);
+            self.balance_checkpoint_count.insert(account, &(count + 1));
+        }
+
+        fn supply_at(&self, timestamp: Timestamp) -> SupplyCheckpoint {
+            let position =
+                checkpoints_until(self.supply_checkpoint_count, timestamp, |index| {
+                    self.supply_checkpoints
+                        .get(index)
+                        .unwrap_or_default()
+                        .timestamp
+                });
+
+            match position {
+                0 => SupplyCheckpoint::default(),
+                index => self.supply_checkpoints.get(index - 1).unwrap_or_default(),
+            }
+        }
+
+        fn write_checkpoint(&mut self, account: AccountId, votes: Balance) {
+            self.env().emit_event(DelegateVotesChanged {
+                delegate: account,
+                previous_votes: self.get_votes(account),
+                new_votes: votes,
+            });
+
+            let timestamp = self.env().block_timestamp();
+            let count = self.checkpoint_count.get(account).unwrap_or_default();
+
+            // Several changes within one block collapse into a single checkpoint
+            if count > 0 {
+                if let Some(last) = self.checkpoints.get((account, count - 1)) {
+                    if last.timestamp == timestamp {
+                        self.checkpoints.insert(
+                            (account, count - 1),
+                            &Checkpoint { timestamp, votes },
+                        );
+                        return
+                    }
+                }
+            }
+
+            self.checkpoints
+                .insert((account, count), &Checkpoint { timestamp, votes });
+            self.checkpoint_count.insert(account, &(count + 1));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn default_accounts() -> ink::env::test::DefaultAccounts<Environment> {
+            ink::env::test::default_accounts::<Environment>()
+        }
+
+        fn alice() -> AccountId {
+            default_accounts().alice
+        }
+
+        fn bob() -> AccountId {
+            default_accounts().bob
+        }
+
+        fn charlie() -> AccountId {
+            default_accounts().charlie
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let contract = GovernanceToken::new(
+                1000,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(psp22::PSP22::total_supply(&contract), 1000);
+            assert_eq!(contract.circulating_supply(), 0);
+        }
+
+        #[ink::test]
+        fn transfer_to_works() {
+            let mut contract = GovernanceToken::new(
+                1000,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(psp22::PSP22::total_supply(&contract), 1000);
+
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+            assert_eq!(contract.circulating_supply(), 10);
+            assert_eq!(psp22::PSP22::balance_of(&contract, alice()), 10);
+        }
+
+        #[ink::test]
+        fn weight_works() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(psp22::PSP22::total_supply(&contract), 100);
+
+            assert_eq!(contract.transfer_to(alice(), 3), Ok(()));
+            assert_eq!(contract.transfer_to(bob(), 1), Ok(()));
+            assert_eq!(contract.weight(alice()), 7_500);
+        }
+
+        #[ink::test]
+        fn weight_keeps_fractional_percentages() {
+            let mut contract = GovernanceToken::new(
+                1000,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+
+            assert_eq!(contract.transfer_to(alice(), 9), Ok(()));
+            assert_eq!(contract.transfer_to(bob(), 991), Ok(()));
+            assert_eq!(contract.weight(alice()), 90);
+        }
+
+        #[ink::test]
+        fn weight_follows_psp22_transfers() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, bob(), 4, Vec::new()),
+                Ok(())
+            );
+
+            // In basis points of the 10 circulating tokens
+            assert_eq!(contract.weight(alice()), 6_000);
+            assert_eq!(contract.weight(bob()), 4_000);
+        }
+
+        #[ink::test]
+        fn transfer_to_is_owner_only_and_bounded() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(
+                contract.transfer_to(bob(), 10),
+                Err(TokenError::Ownable(OwnableError::CallerIsNotOwner))
+            );
+
+            ink::env::test::set_caller::<Environment>(alice());
+            assert_eq!(
+                contract.transfer_to(bob(), 101),
+                Err(TokenError::InsufficientReserve)
+            );
+            assert_eq!(contract.transfer_to(bob(), 100), Ok(()));
+            assert_eq!(contract.circulating_supply(), 100);
+        }
+
+        #[ink::test]
+        fn mint_works() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+
+            assert_eq!(contract.mint(bob(), 100), Ok(()));
+            assert_eq!(psp22::PSP22::total_supply(&contract), 200);
+            assert_eq!(contract.circulating_supply(), 100);
+            assert_eq!(contract.weight(bob()), 10_000);
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(
+                contract.mint(bob(), 100),
+                Err(TokenError::Ownable(OwnableError::CallerIsNotOwner))
+            );
+        }
+
+        #[ink::test]
+        fn burn_works() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+
+            assert_eq!(contract.burn(4), Ok(()));
+            assert_eq!(psp22::PSP22::total_supply(&contract), 96);
+            assert_eq!(contract.circulating_supply(), 6);
+            assert_eq!(psp22::PSP22::balance_of(&contract, alice()), 6);
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(
+                contract.burn_from(alice(), 2),
+                Err(TokenError::PSP22(PSP22Error::InsufficientAllowance))
+            );
+
+            ink::env::test::set_caller::<Environment>(alice());
+            assert_eq!(psp22::PSP22::approve(&mut contract, bob(), 2), Ok(()));
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(contract.burn_from(alice(), 2), Ok(()));
+            assert_eq!(psp22::PSP22::total_supply(&contract), 94);
+            assert_eq!(psp22::PSP22::allowance(&contract, alice(), bob()), 0);
+        }
+
+        #[ink::test]
+        fn mint_respects_cap() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                Some(150),
+            );
+            assert_eq!(contract.cap(), Some(150));
+
+            assert_eq!(contract.mint(bob(), 51), Err(TokenError::CapExceeded));
+            assert_eq!(contract.mint(bob(), 50), Ok(()));
+            assert_eq!(psp22::PSP22::total_supply(&contract), 150);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "Initial supply exceeds cap")]
+        fn new_rejects_supply_above_cap() {
+            GovernanceToken::new(
+                200,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                Some(150),
+            );
+        }
+
+        #[ink::test]
+        fn permit_rejects_expired_and_forged_signatures() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+
+            ink::env::test::set_block_timestamp::<Environment>(10);
+            assert_eq!(
+                contract.permit(alice(), bob(), 5, 9, [0; 65]),
+                Err(TokenError::PermitExpired)
+            );
+            assert_eq!(
+                contract.permit(alice(), bob(), 5, 10, [0; 65]),
+                Err(TokenError::PermitInvalidSignature)
+            );
+            assert_eq!(contract.nonces(alice()), 0);
+            assert_eq!(psp22::PSP22::allowance(&contract, alice(), bob()), 0);
+        }
+
+        #[ink::test]
+        fn permit_approves_with_a_valid_signature() {
+            use secp256k1::{
+                Message,
+                PublicKey,
+                SecretKey,
+                SECP256K1,
+            };
+
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+
+            let secret_key = SecretKey::from_slice(&[7; 32]).unwrap();
+            let public_key = PublicKey::from_secret_key(SECP256K1, &secret_key);
+            let mut owner = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&public_key.serialize(), &mut owner);
+            let owner = AccountId::from(owner);
+
+            let sign = |digest: [u8; 32]| {
+                let message = Message::from_slice(&digest).unwrap();
+                let (recovery_id, compact) = SECP256K1
+                    .sign_ecdsa_recoverable(&message, &secret_key)
+                    .serialize_compact();
+                let mut signature = [0u8; 65];
+                signature[..64].copy_from_slice(&compact);
+                signature[64] = recovery_id.to_i32() as u8;
+                signature
+            };
+
+            ink::env::test::set_block_timestamp::<Environment>(10);
+            let signature = sign(contract.permit_digest(owner, bob(), 5, 10));
+            assert_eq!(contract.permit(owner, bob(), 5, 10, signature), Ok(()));
+            assert_eq!(psp22::PSP22::allowance(&contract, owner, bob()), 5);
+            assert_eq!(contract.nonces(owner), 1);
+
+            // The nonce is spent, so the signature cannot be replayed
+            assert_eq!(
+                contract.permit(owner, bob(), 5, 10, signature),
+                Err(TokenError::PermitInvalidSignature)
+            );
+            assert_eq!(contract.nonces(owner), 1);
+        }
+
+        #[ink::test]
+        fn transfer_batch_is_all_or_nothing() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+
+            assert_eq!(
+                contract.transfer_batch(vec![(bob(), 6), (charlie(), 5)]),
+                Err(TokenError::PSP22(PSP22Error::InsufficientBalance))
+            );
+            assert_eq!(psp22::PSP22::balance_of(&contract, bob()), 0);
+
+            assert_eq!(
+                contract.transfer_batch(vec![(bob(), 6), (charlie(), 4)]),
+                Ok(())
+            );
+            assert_eq!(psp22::PSP22::balance_of(&contract, alice()), 0);
+            assert_eq!(psp22::PSP22::balance_of(&contract, bob()), 6);
+            assert_eq!(psp22::PSP22::balance_of(&contract, charlie()), 4);
+            // Mint, distribution with its transfer, and one transfer per recipient
+            assert_eq!(ink::env::test::recorded_events().count(), 5);
+        }
+
+        #[ink::test]
+        fn delegation_checkpoints_follow_transfers() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(contract.delegate(alice()), Ok(()));
+
+            ink::env::test::set_block_timestamp::<Environment>(10);
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+            assert_eq!(contract.get_votes(alice()), 10);
+
+            ink::env::test::set_block_timestamp::<Environment>(20);
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, bob(), 4, Vec::new()),
+                Ok(())
+            );
+            assert_eq!(contract.burn(1), Ok(()));
+            assert_eq!(contract.get_votes(alice()), 5);
+            assert_eq!(contract.get_votes(bob()), 0);
+
+            ink::env::test::set_block_timestamp::<Environment>(30);
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(contract.delegate(alice()), Ok(()));
+            assert_eq!(contract.delegates(bob()), Some(alice()));
+            assert_eq!(contract.get_votes(alice()), 9);
+
+            assert_eq!(contract.get_past_votes(alice(), 5), 0);
+            assert_eq!(contract.get_past_votes(alice(), 15), 10);
+            assert_eq!(contract.get_past_votes(alice(), 25), 5);
+            assert_eq!(contract.get_past_votes(alice(), 30), 9);
+            assert_eq!(contract.vote_weight(alice()), 10_000);
+            assert_eq!(contract.vote_weight(bob()), 0);
+
+            // Balances keep their own history, regardless of delegation
+            assert_eq!(contract.balance_of_at(bob(), 15), 0);
+            assert_eq!(contract.balance_of_at(bob(), 20), 4);
+            assert_eq!(contract.balance_of_at(alice(), 15), 10);
+            assert_eq!(contract.balance_of_at(alice(), 30), 5);
+        }
+
+        #[ink::test]
+        fn supply_history_works() {
+            ink::env::test::set_block_timestamp::<Environment>(10);
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+
+            ink::env::test::set_block_timestamp::<Environment>(20);
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+
+            ink::env::test::set_block_timestamp::<Environment>(30);
+            assert_eq!(contract.mint(bob(), 50), Ok(()));
+            assert_eq!(contract.burn(5), Ok(()));
+
+            assert_eq!(contract.total_supply_at(5), 0);
+            assert_eq!(contract.total_supply_at(15), 100);
+            assert_eq!(contract.circulating_supply_at(15), 0);
+            assert_eq!(contract.circulating_supply_at(25), 10);
+            assert_eq!(contract.total_supply_at(30), 145);
+            assert_eq!(contract.circulating_supply_at(30), 55);
+        }
+
+        #[ink::test]
+        fn metadata_updates_are_owner_only() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+
+            assert_eq!(contract.set_name(Some("DaoCoin".into())), Ok(()));
+            assert_eq!(contract.set_symbol(Some("DAO".into())), Ok(()));
+            assert_eq!(
+                contract.set_metadata_uri(Some("ipfs://branding".into())),
+                Ok(())
+            );
+            assert_eq!(
+                metadata::PSP22Metadata::token_name(&contract),
+                Some(String::from("DaoCoin"))
+            );
+            assert_eq!(
+                metadata::PSP22Metadata::token_symbol(&contract),
+                Some(String::from("DAO"))
+            );
+            assert_eq!(
+                contract.metadata_uri(),
+                Some(String::from("ipfs://branding"))
+            );
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(
+                contract.set_name(None),
+                Err(TokenError::Ownable(OwnableError::CallerIsNotOwner))
+            );
+        }
+
+        #[ink::test]
+        fn delegation_emits_events() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 3);
+
+            assert_eq!(contract.delegate(alice()), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 5);
+
+            assert_eq!(psp22::PSP22::approve(&mut contract, bob(), 5), Ok(()));
+            assert_eq!(ink::env::test::recorded_events().count(), 6);
+        }
+
+        #[ink::test]
+        fn holders_index_works() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(contract.holder_count(), 0);
+
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+            assert_eq!(contract.transfer_to(bob(), 10), Ok(()));
+            assert_eq!(contract.transfer_to(charlie(), 10), Ok(()));
+            assert_eq!(contract.holders(0, 10), vec![alice(), bob(), charlie()]);
+
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, bob(), 10, Vec::new()),
+                Ok(())
+            );
+            assert_eq!(contract.holder_count(), 2);
+            assert_eq!(contract.holders(0, 10), vec![charlie(), bob()]);
+            assert_eq!(contract.holders(1, 1), vec![bob()]);
+
+            // One page holds at most `MAX_PAGE_SIZE` holders
+            for index in 2..150 {
+                contract.holders.insert(index, &alice());
+            }
+            contract.holder_count = 150;
+            assert_eq!(contract.holders(0, u32::MAX).len(), MAX_PAGE_SIZE as usize);
+        }
+
+        #[ink::test]
+        fn frozen_accounts_cannot_transfer() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(
+                contract.set_frozen(alice(), true),
+                Err(TokenError::Unauthorized)
+            );
+
+            ink::env::test::set_caller::<Environment>(alice());
+            assert_eq!(
+                access_control::AccessControl::grant_role(
+                    &mut contract,
+                    COMPLIANCE,
+                    Some(bob())
+                ),
+                Ok(())
+            );
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(contract.set_frozen(alice(), true), Ok(()));
+            assert!(contract.is_frozen(alice()));
+
+            ink::env::test::set_caller::<Environment>(alice());
+            let frozen = Err(PSP22Error::Custom(String::from("AccountFrozen")));
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, bob(), 1, Vec::new()),
+                frozen
+            );
+            assert_eq!(
+                contract.transfer_to(alice(), 1),
+                Err(TokenError::PSP22(PSP22Error::Custom(String::from(
+                    "AccountFrozen"
+                ))))
+            );
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(contract.set_frozen(alice(), false), Ok(()));
+
+            ink::env::test::set_caller::<Environment>(alice());
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, bob(), 1, Vec::new()),
+                Ok(())
+            );
+        }
+
+        #[ink::test]
+        fn restricted_transfers_need_whitelisted_accounts() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                None,
+            );
+            assert_eq!(contract.transfer_to(alice(), 10), Ok(()));
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(
+                contract.set_transfers_restricted(true),
+                Err(TokenError::Unauthorized)
+            );
+
+            ink::env::test::set_caller::<Environment>(alice());
+            assert_eq!(contract.set_transfers_restricted(true), Ok(()));
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, bob(), 1, Vec::new()),
+                Err(PSP22Error::Custom(String::from("SenderNotWhitelisted")))
+            );
+
+            assert_eq!(contract.set_whitelisted(alice(), true), Ok(()));
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, bob(), 1, Vec::new()),
+                Err(PSP22Error::Custom(String::from("RecipientNotWhitelisted")))
+            );
+            assert_eq!(
+                contract.transfer_to(bob(), 1),
+                Err(TokenError::PSP22(PSP22Error::Custom(String::from(
+                    "RecipientNotWhitelisted"
+                ))))
+            );
+
+            assert_eq!(contract.set_whitelisted(bob(), true), Ok(()));
+            assert!(contract.is_whitelisted(bob()));
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, bob(), 1, Vec::new()),
+                Ok(())
+            );
+            assert_eq!(contract.transfer_to(bob(), 1), Ok(()));
+        }
+
+        #[ink::test]
+        fn emissions_decay_per_epoch() {
+            let mut contract = GovernanceToken::new(
+                100,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                8,
+                Some(290),
+            );
+            assert_eq!(contract.emit_rewards(), Err(TokenError::NoEmissionSchedule));
+
+            let schedule = EmissionSchedule {
+                rewards_pool: charlie(),
+                initial_emission: 100,
+                decay: 5_000,
+                epoch_length: 10,
+            };
+            assert_eq!(contract.set_emission_schedule(Some(schedule)), Ok(()));
+
+            ink::env::test::set_block_timestamp::<Environment>(35);
+            assert_eq!(contract.emit_rewards(), Ok(175));
+            assert_eq!(psp22::PSP22::balance_of(&contract, charlie()), 175);
+            assert_eq!(contract.emit_rewards(), Ok(0));
+
+            // Seven more epochs would emit 22, but only 15 are left under the cap
+            ink::env::test::set_block_timestamp::<Environment>(100);
+            assert_eq!(contract.emit_rewards(), Ok(15));
+            assert_eq!(psp22::PSP22::total_supply(&contract), 290);
+
+            // A huge emission decays without overflowing
+            let schedule = EmissionSchedule {
+                initial_emission: Balance::MAX,
+                ..schedule
+            };
+            assert_eq!(contract.set_emission_schedule(Some(schedule)), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(110);
+            assert_eq!(contract.emit_rewards(), Ok(0));
+            assert_eq!(
+                contract.next_emission,
+                Balance::MAX / BASIS_POINTS as Balance
+            );
+
+            ink::env::test::set_caller::<Environment>(bob());
+            assert_eq!(
+                contract.set_emission_schedule(None),
+                Err(TokenError::Ownable(OwnableError::CallerIsNotOwner))
+            );
+        }
+
+        #[ink::test]
+        fn weight_conversion_uses_decimals() {
+            // 1000 whole tokens with two decimals, half of them distributed
+            let mut contract = GovernanceToken::new(
+                100_000,
+                Some("VoteCoin".into()),
+                Some("VCT".into()),
+                2,
+                None,
+            );
+            assert_eq!(contract.transfer_to(alice(), 50_000), Ok(()));
 
-            contract.transfer_to(alice(), 3);
-            assert_eq!(contract.weight(alice()), 3);
+            assert_eq!(contract.tokens_to_weight(10), 200);
+            assert_eq!(contract.weight_to_tokens(200), 10);
+            assert_eq!(contract.weight_to_tokens(BASIS_POINTS), 500);
         }
     }
 }
@@ -0,0 +1,99 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+pub use self::share_class::ShareClassRef;
+
+/// Additional class of shares of the DAO, e.g. non-voting preferred shares, issued
+/// by the Governor. It is a plain PSP22 token without vote checkpoints, so it never
+/// counts toward voting weight.
+#[openbrush::implementation(PSP22, PSP22Metadata)]
+#[openbrush::contract]
+mod share_class {
+    use openbrush::traits::Storage;
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct ShareClass {
+        #[storage_field]
+        psp22: psp22::Data,
+
+        #[storage_field]
+        metadata: metadata::Data,
+
+        /// Governor that issued the class.
+        issuer: AccountId,
+    }
+
+    #[overrider(psp22::Internal)]
+    fn _emit_transfer_event(
+        &self,
+        from: Option<AccountId>,
+        to: Option<AccountId>,
+        amount: Balance,
+    ) {
+        self.env().emit_event(Transfer {
+            from,
+            to,
+            value: amount,
+        });
+    }
+
+    impl ShareClass {
+        /// Mints the whole `supply` to `holder`. The caller becomes the issuer.
+        #[ink(constructor)]
+        pub fn new(
+            supply: Balance,
+            holder: AccountId,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimal: u8,
+        ) -> Self {
+            let mut _instance = Self::default();
+
+            _instance.metadata.name.set(&name);
+            _instance.metadata.symbol.set(&symbol);
+            _instance.metadata.decimals.set(&decimal);
+
+            _instance.issuer = Self::env().caller();
+            psp22::Internal::_mint_to(&mut _instance, holder, supply)
+                .expect("Should mint");
+
+            _instance
+        }
+
+        #[ink(message)]
+        pub fn issuer(&self) -> AccountId {
+            self.issuer
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn new_mints_the_supply_to_the_holder() {
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let contract = ShareClass::new(
+                1_000,
+                accounts.bob,
+                Some("Preferred".into()),
+                Some("PREF".into()),
+                0,
+            );
+
+            assert_eq!(contract.issuer(), accounts.alice);
+            assert_eq!(psp22::PSP22::total_supply(&contract), 1_000);
+            assert_eq!(psp22::PSP22::balance_of(&contract, accounts.bob), 1_000);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::snapshot_registry::SnapshotRegistryRef;
+
+/// Keeps Merkle roots of voting weights per snapshot, so that a Governor can verify
+/// the weights claimed by voters without the token storing checkpoints.
+#[ink::contract]
+mod snapshot_registry {
+    use ink::{
+        env::hash::Blake2x256,
+        prelude::vec::Vec,
+        storage::Mapping,
+    };
+    use scale::Encode;
+
+    pub type SnapshotId = u64;
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum RegistryError {
+        Unauthorized,
+        SnapshotNotFound,
+        RootAlreadyPublished,
+    }
+
+    /// Weights of all accounts at `timestamp`, committed to as a Merkle root.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Snapshot {
+        /// Account that requested the snapshot, e.g. a Governor.
+        registrar: AccountId,
+        timestamp: Timestamp,
+        /// Root over the leaves `blake2x256((account, weight).encode())`, with every
+        /// pair of nodes hashed in ascending order.
+        root: Option<[u8; 32]>,
+        /// Sum of all weights in the tree.
+        total_weight: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SnapshotRegistered {
+        #[ink(topic)]
+        snapshot_id: SnapshotId,
+        #[ink(topic)]
+        registrar: AccountId,
+        timestamp: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct RootPublished {
+        #[ink(topic)]
+        snapshot_id: SnapshotId,
+        root: [u8; 32],
+        total_weight: Balance,
+    }
+
+    #[ink(storage)]
+    pub struct SnapshotRegistry {
+        /// Account computing the trees off-chain and publishing their roots.
+        publisher: AccountId,
+        snapshots: Mapping<SnapshotId, Snapshot>,
+        next_snapshot_id: SnapshotId,
+    }
+
+    impl SnapshotRegistry {
+        #[ink(constructor)]
+        pub fn new(publisher: AccountId) -> Self {
+            Self {
+                publisher,
+                snapshots: Mapping::default(),
+                next_snapshot_id: 0,
+            }
+        }
+
+        #[ink(message)]
+        pub fn publisher(&self) -> AccountId {
+            self.publisher
+        }
+
+        /// Requests a snapshot of the weights at `timestamp`. Anyone can register
+        /// one; the publisher publishes its root once `timestamp` has passed.
+        #[ink(message)]
+        pub fn register(&mut self, timestamp: Timestamp) -> SnapshotId {
+            let snapshot_id = self.next_snapshot_id;
+            let registrar = self.env().caller();
+
+            self.snapshots.insert(
+                snapshot_id,
+                &Snapshot {
+                    registrar,
+                    timestamp,
+                    root: None,
+                    total_weight: 0,
+                },
+            );
+            self.next_snapshot_id += 1;
+
+            self.env().emit_event(SnapshotRegistered {
+                snapshot_id,
+                registrar,
+                timestamp,
+            });
+
+            snapshot_id
+        }
+
+        /// Publishes the Merkle root of a snapshot. Only callable by the publisher,
+        /// once per snapshot.
+        #[ink(message)]
+        pub fn publish_root(
+            &mut self,
+            snapshot_id: SnapshotId,
+            root: [u8; 32],
+            total_weight: Balance,
+        ) -> Result<(), RegistryError> {
+            if self.env().caller() != self.publisher {
+                return Err(RegistryError::Unauthorized)
+            }
+
+            let mut snapshot = match self.snapshots.get(snapshot_id) {
+                Some(value) => value,
+                None => return Err(RegistryError::SnapshotNotFound),
+            };
+
+            if snapshot.root.is_some() {
+                return Err(RegistryError::RootAlreadyPublished)
+            }
+
+            snapshot.root = Some(root);
+            snapshot.total_weight = total_weight;
+            self.snapshots.insert(snapshot_id, &snapshot);
+
+            self.env().emit_event(RootPublished {
+                snapshot_id,
+                root,
+                total_weight,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn snapshot(&self, snapshot_id: SnapshotId) -> Option<Snapshot> {
+            self.snapshots.get(snapshot_id)
+        }
+
+        /// Returns the sum of all weights of a snapshot, once its root is published.
+        #[ink(message)]
+        pub fn total_weight(&self, snapshot_id: SnapshotId) -> Option<Balance> {
+            self.snapshots
+                .get(snapshot_id)
+                .filter(|snapshot| snapshot.root.is_some())
+                .map(|snapshot| snapshot.total_weight)
+        }
+
+        /// Whether `proof` shows that `account` has `weight` in a published snapshot.
+        #[ink(message)]
+        pub fn verify(
+            &self,
+            snapshot_id: SnapshotId,
+            account: AccountId,
+            weight: Balance,
+            proof: Vec<[u8; 32]>,
+        ) -> bool {
+            let root = match self.snapshots.get(snapshot_id).and_then(|s| s.root) {
+                Some(value) => value,
+                None => return false,
+            };
+
+            let mut node = Self::hash(&(account, weight).encode());
+            for sibling in proof {
+                node = Self::hash_pair(node, sibling);
+            }
+
+            node == root
+        }
+
+        /// Hashes two nodes in ascending order, so that proofs need no directions.
+        fn hash_pair(left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+            let (first, second) = if left <= right {
+                (left, right)
+            } else {
+                (right, left)
+            };
+
+            let mut input = [0u8; 64];
+            input[..32].copy_from_slice(&first);
+            input[32..].copy_from_slice(&second);
+            Self::hash(&input)
+        }
+
+        fn hash(input: &[u8]) -> [u8; 32] {
+            let mut output = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(input, &mut output);
+            output
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn default_accounts() -> ink::env::test::DefaultAccounts<Environment> {
+            ink::env::test::default_accounts::<Environment>()
+        }
+
+        fn leaf(account: AccountId, weight: Balance) -> [u8; 32] {
+            SnapshotRegistry::hash(&(account, weight).encode())
+        }
+
+        #[ink::test]
+        fn only_the_publisher_publishes_roots() {
+            let accounts = default_accounts();
+            let mut registry = SnapshotRegistry::new(accounts.bob);
+
+            assert_eq!(registry.register(100), 0);
+            assert_eq!(
+                registry.publish_root(0, [0; 32], 10),
+                Err(RegistryError::Unauthorized)
+            );
+
+            ink::env::test::set_caller::<Environment>(accounts.bob);
+            assert_eq!(
+                registry.publish_root(1, [0; 32], 10),
+                Err(RegistryError::SnapshotNotFound)
+            );
+            assert_eq!(registry.total_weight(0), None);
+            assert_eq!(registry.publish_root(0, [0; 32], 10), Ok(()));
+            assert_eq!(registry.total_weight(0), Some(10));
+            assert_eq!(
+                registry.publish_root(0, [1; 32], 10),
+                Err(RegistryError::RootAlreadyPublished)
+            );
+            assert_eq!(registry.snapshot(0).unwrap().registrar, accounts.alice);
+        }
+
+        #[ink::test]
+        fn verify_checks_merkle_proofs() {
+            let accounts = default_accounts();
+            let mut registry = SnapshotRegistry::new(accounts.alice);
+
+            let leaves = [
+                leaf(accounts.alice, 30),
+                leaf(accounts.bob, 50),
+                leaf(accounts.charlie, 20),
+            ];
+            let left = SnapshotRegistry::hash_pair(leaves[0], leaves[1]);
+            let root = SnapshotRegistry::hash_pair(left, leaves[2]);
+
+            registry.register(100);
+            assert!(!registry.verify(0, accounts.charlie, 20, Vec::from([left])));
+            assert_eq!(registry.publish_root(0, root, 100), Ok(()));
+
+            assert!(registry.verify(
+                0,
+                accounts.alice,
+                30,
+                Vec::from([leaves[1], leaves[2]])
+            ));
+            assert!(registry.verify(0, accounts.charlie, 20, Vec::from([left])));
+            assert!(!registry.verify(0, accounts.charlie, 21, Vec::from([left])));
+            assert!(!registry.verify(0, accounts.bob, 50, Vec::from([left])));
+        }
+    }
+}
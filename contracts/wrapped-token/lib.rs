@@ -0,0 +1,614 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+pub use self::wrapped_token::WrappedTokenRef;
+
+/// Wraps an existing PSP22 token 1:1 into a token with delegation, vote, balance and
+/// supply checkpoints, so that it can serve as the Governor's governance token or as
+/// one of its voting assets.
+#[openbrush::implementation(PSP22, PSP22Metadata)]
+#[openbrush::contract]
+mod wrapped_token {
+
+    use governance_token::checkpoints_until;
+    use ink::{
+        env::{
+            call::{
+                build_call,
+                ExecutionInput,
+                Selector,
+            },
+            DefaultEnvironment,
+        },
+        prelude::vec::Vec,
+        storage::Mapping,
+    };
+    use openbrush::{
+        contracts::psp22::PSP22Error,
+        traits::Storage,
+    };
+
+    /// Voting weight of the whole supply, i.e. weights are in basis points.
+    pub const BASIS_POINTS: u64 = 10_000;
+
+    /// Weight limits of a transfer of the underlying token.
+    const TRANSFER_REF_TIME: u64 = 5_000_000_000;
+    const TRANSFER_PROOF_SIZE: u64 = 256 * 1024;
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum WrapperError {
+        ZeroAmount,
+        UnderlyingTransferFailed,
+        PSP22(PSP22Error),
+    }
+
+    impl From<PSP22Error> for WrapperError {
+        fn from(error: PSP22Error) -> Self {
+            WrapperError::PSP22(error)
+        }
+    }
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DelegateChanged {
+        #[ink(topic)]
+        delegator: AccountId,
+        from_delegate: Option<AccountId>,
+        to_delegate: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DelegateVotesChanged {
+        #[ink(topic)]
+        delegate: AccountId,
+        previous_votes: Balance,
+        new_votes: Balance,
+    }
+
+    /// Delegated voting power of an account from `timestamp` on.
+    #[derive(
+        Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Checkpoint {
+        timestamp: Timestamp,
+        votes: Balance,
+    }
+
+    /// Balance of an account from `timestamp` on.
+    #[derive(
+        Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct BalanceCheckpoint {
+        timestamp: Timestamp,
+        balance: Balance,
+    }
+
+    /// Total supply from `timestamp` on.
+    #[derive(
+        Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SupplyCheckpoint {
+        timestamp: Timestamp,
+        total_supply: Balance,
+    }
+
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct WrappedToken {
+        #[storage_field]
+        psp22: psp22::Data,
+
+        #[storage_field]
+        metadata: metadata::Data,
+
+        /// The wrapped PSP22 token.
+        underlying: AccountId,
+
+        delegates: Mapping<AccountId, AccountId>,
+
+        /// Append-only history of the delegated votes of every delegatee.
+        checkpoints: Mapping<(AccountId, u32), Checkpoint>,
+
+        checkpoint_count: Mapping<AccountId, u32>,
+
+        supply_checkpoints: Mapping<u32, SupplyCheckpoint>,
+
+        supply_checkpoint_count: u32,
+
+        /// Append-only history of the balance of every account, so that holders who
+        /// delegated can still vote directly with their balance at a snapshot.
+        balance_checkpoints: Mapping<(AccountId, u32), BalanceCheckpoint>,
+
+        balance_checkpoint_count: Mapping<AccountId, u32>,
+    }
+
+    #[overrider(psp22::Internal)]
+    fn _emit_transfer_event(
+        &self,
+        from: Option<AccountId>,
+        to: Option<AccountId>,
+        amount: Balance,
+    ) {
+        self.env().emit_event(Transfer {
+            from,
+            to,
+            value: amount,
+        });
+    }
+
+    // Keeps delegated votes, balances and the supply in sync with every transfer,
+    // deposit and withdrawal
+    #[overrider(psp22::Internal)]
+    fn _after_token_transfer(
+        &mut self,
+        from: Option<&AccountId>,
+        to: Option<&AccountId>,
+        amount: &Balance,
+    ) -> Result<(), PSP22Error> {
+        let from_delegate = from.and_then(|account| self.delegates.get(account));
+        let to_delegate = to.and_then(|account| self.delegates.get(account));
+
+        self.move_voting_power(from_delegate, to_delegate, *amount);
+
+        if let Some(from) = from {
+            self.checkpoint_balance(*from);
+        }
+        if let Some(to) = to {
+            self.checkpoint_balance(*to);
+        }
+        if from.is_none() || to.is_none() {
+            self.checkpoint_supply();
+        }
+
+        Ok(())
+    }
+
+    impl WrappedToken {
+        #[ink(constructor)]
+        pub fn new(
+            underlying: AccountId,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimal: u8,
+        ) -> Self {
+            let mut _instance = Self::default();
+
+            _instance.metadata.name.set(&name);
+            _instance.metadata.symbol.set(&symbol);
+            _instance.metadata.decimals.set(&decimal);
+
+            _instance.underlying = underlying;
+
+            _instance
+        }
+
+        #[ink(message)]
+        pub fn underlying(&self) -> AccountId {
+            self.underlying
+        }
+
+        /// Pulls `amount` of the underlying token from the caller, who has to approve
+        /// this contract first, and mints as many wrapped tokens to `account`.
+        #[ink(message)]
+        pub fn deposit_for(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), WrapperError> {
+            if amount == 0 {
+                return Err(WrapperError::ZeroAmount)
+            }
+
+            let caller = self.env().caller();
+            let contract = self.env().account_id();
+            self.call_underlying(
+                ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                    "PSP22::transfer_from"
+                )))
+                .push_arg(caller)
+                .push_arg(contract)
+                .push_arg(amount)
+                .push_arg(Vec::<u8>::new()),
+            )?;
+
+            psp22::Internal::_mint_to(self, account, amount)?;
+
+            Ok(())
+        }
+
+        /// Burns `amount` of the caller's wrapped tokens and sends as many underlying
+        /// tokens to `account`.
+        #[ink(message)]
+        pub fn withdraw_to(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), WrapperError> {
+            if amount == 0 {
+                return Err(WrapperError::ZeroAmount)
+            }
+
+            let caller = self.env().caller();
+            psp22::Internal::_burn_from(self, caller, amount)?;
+
+            self.call_underlying(
+                ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                    "PSP22::transfer"
+                )))
+                .push_arg(account)
+                .push_arg(amount)
+                .push_arg(Vec::<u8>::new()),
+            )
+        }
+
+        /// Delegates the caller's voting power, including future balance changes, to
+        /// `delegatee`. Holders have to delegate to themselves to vote directly.
+        #[ink(message)]
+        pub fn delegate(&mut self, delegatee: AccountId) -> Result<(), WrapperError> {
+            let caller = self.env().caller();
+            let previous = self.delegates.get(caller);
+
+            self.delegates.insert(caller, &delegatee);
+            self.env().emit_event(DelegateChanged {
+                delegator: caller,
+                from_delegate: previous,
+                to_delegate: delegatee,
+            });
+
+            let balance = psp22::Internal::_balance_of(self, &caller);
+            self.move_voting_power(previous, Some(delegatee), balance);
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn delegates(&self, account: AccountId) -> Option<AccountId> {
+            self.delegates.get(account)
+        }
+
+        /// Returns the voting power currently delegated to `account`.
+        #[ink(message)]
+        pub fn get_votes(&self, account: AccountId) -> Balance {
+            match self.checkpoint_count.get(account).unwrap_or_default() {
+                0 => 0,
+                count => {
+                    self.checkpoints
+                        .get((account, count - 1))
+                        .unwrap_or_default()
+                        .votes
+                }
+            }
+        }
+
+        /// Returns the voting power delegated to `account` at `timestamp`.
+        #[ink(message)]
+        pub fn get_past_votes(
+            &self,
+            account: AccountId,
+            timestamp: Timestamp,
+        ) -> Balance {
+            let count = self.checkpoint_count.get(account).unwrap_or_default();
+            let position = checkpoints_until(count, timestamp, |index| {
+                self.checkpoints
+                    .get((account, index))
+                    .unwrap_or_default()
+                    .timestamp
+            });
+
+            match position {
+                0 => 0,
+                index => {
+                    self.checkpoints
+                        .get((account, index - 1))
+                        .unwrap_or_default()
+                        .votes
+                }
+            }
+        }
+
+        /// Returns the balance of `account` at `timestamp`.
+        #[ink(message)]
+        pub fn balance_of_at(&self, account: AccountId, timestamp: Timestamp) -> Balance {
+            let count = self
+                .balance_checkpoint_count
+                .get(account)
+                .unwrap_or_default();
+            let position = checkpoints_until(count, timestamp, |index| {
+                self.balance_checkpoints
+                    .get((account, index))
+                    .unwrap_or_default()
+                    .timestamp
+            });
+
+            match position {
+                0 => 0,
+                index => {
+                    self.balance_checkpoints
+                        .get((account, index - 1))
+                        .unwrap_or_default()
+                        .balance
+                }
+            }
+        }
+
+        /// Returns the total supply at `timestamp`.
+        #[ink(message)]
+        pub fn total_supply_at(&self, timestamp: Timestamp) -> Balance {
+            let position =
+                checkpoints_until(self.supply_checkpoint_count, timestamp, |index| {
+                    self.supply_checkpoints
+                        .get(index)
+                        .unwrap_or_default()
+                        .timestamp
+                });
+
+            match position {
+                0 => 0,
+                index => {
+                    self.supply_checkpoints
+                        .get(index - 1)
+                        .unwrap_or_default()
+                        .total_supply
+                }
+            }
+        }
+
+        /// Returns the circulating supply at `timestamp`. The wrapper keeps no reserve,
+        /// so it is the total supply.
+        #[ink(message)]
+        pub fn circulating_supply_at(&self, timestamp: Timestamp) -> Balance {
+            self.total_supply_at(timestamp)
+        }
+
+        /// Returns the share of the wrapped supply held by `account`, in basis points,
+        /// matching the governance token's `weight`.
+        #[ink(message)]
+        pub fn weight(&self, account: AccountId) -> u64 {
+            let total_supply = psp22::Internal::_total_supply(self);
+            if total_supply == 0 {
+                return 0
+            }
+
+            let balance = psp22::Internal::_balance_of(self, &account);
+            (balance.saturating_mul(BASIS_POINTS as Balance) / total_supply) as u64
+        }
+
+        fn call_underlying<Args>(
+            &self,
+            input: ExecutionInput<Args>,
+        ) -> Result<(), WrapperError>
+        where
+            Args: scale::Encode,
+        {
+            match build_call::<DefaultEnvironment>()
+                .call_v2(self.underlying)
+                .ref_time_limit(TRANSFER_REF_TIME)
+                .proof_size_limit(TRANSFER_PROOF_SIZE)
+                .exec_input(input)
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke()
+            {
+                Ok(Ok(Ok(()))) => Ok(()),
+                Ok(Ok(Err(error))) => Err(error.into()),
+                _ => Err(WrapperError::UnderlyingTransferFailed),
+            }
+        }
+
+        fn move_voting_power(
+            &mut self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            amount: Balance,
+        ) {
+            if from == to || amount == 0 {
+                return
+            }
+
+            if let Some(from) = from {
+                let votes = self.get_votes(from).saturating_sub(amount);
+                self.write_checkpoint(from, votes);
+            }
+
+            if let Some(to) = to {
+                let votes = self.get_votes(to).saturating_add(amount);
+                self.write_checkpoint(to, votes);
+            }
+        }
+
+        fn checkpoint_supply(&mut self) {
+            let checkpoint = SupplyCheckpoint {
+                timestamp: self.env().block_timestamp(),
+                total_supply: psp22::Internal::_total_supply(self),
+            };
+            let count = self.supply_checkpoint_count;
+
+            // Several changes within one block collapse into a single checkpoint
+            if count > 0 {
+                if let Some(last) = self.supply_checkpoints.get(count - 1) {
+                    if last.timestamp == checkpoint.timestamp {
+                        self.supply_checkpoints.insert(count - 1, &checkpoint);
+                        return
+                    }
+                }
+            }
+
+            self.supply_checkpoints.insert(count, &checkpoint);
+            self.supply_checkpoint_count = count + 1;
+        }
+
+        fn checkpoint_balance(&mut self, account: AccountId) {
+            let checkpoint = BalanceCheckpoint {
+                timestamp: self.env().block_timestamp(),
+                balance: psp22::Internal::_balance_of(self, &account),
+            };
+            let count = self
+                .balance_checkpoint_count
+                .get(account)
+                .unwrap_or_default();
+
+            // Several changes within one block collapse into a single checkpoint
+            if count > 0 {
+                if let Some(last) = self.balance_checkpoints.get((account, count - 1)) {
+                    if last.timestamp == checkpoint.timestamp {
+                        self.balance_checkpoints
+                            .insert((account, count - 1), &checkpoint);
+                        return
+                    }
+                }
+            }
+
+            self.balance_checkpoints
+                .insert((account, count), &checkpoint);
+            self.balance_checkpoint_count.insert(account, &(count + 1));
+        }
+
+        fn write_checkpoint(&mut self, account: AccountId, votes: Balance) {
+            self.env().emit_event(DelegateVotesChanged {
+                delegate: account,
+                previous_votes: self.get_votes(account),
+                new_votes: votes,
+            });
+
+            let timestamp = self.env().block_timestamp();
+            let count = self.checkpoint_count.get(account).unwrap_or_default();
+
+            // Several changes within one block collapse into a single checkpoint
+            if count > 0 {
+                if let Some(last) = self.checkpoints.get((account, count - 1)) {
+                    if last.timestamp == timestamp {
+                        self.checkpoints.insert(
+                            (account, count - 1),
+                            &Checkpoint { timestamp, votes },
+                        );
+                        return
+                    }
+                }
+            }
+
+            self.checkpoints
+                .insert((account, count), &Checkpoint { timestamp, votes });
+            self.checkpoint_count.insert(account, &(count + 1));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn default_accounts() -> ink::env::test::DefaultAccounts<Environment> {
+            ink::env::test::default_accounts::<Environment>()
+        }
+
+        fn create_contract() -> WrappedToken {
+            WrappedToken::new(
+                AccountId::from([0x01; 32]),
+                Some("Wrapped VoteCoin".into()),
+                Some("wVCT".into()),
+                8,
+            )
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let contract = create_contract();
+            assert_eq!(contract.underlying(), AccountId::from([0x01; 32]));
+            assert_eq!(psp22::PSP22::total_supply(&contract), 0);
+        }
+
+        #[ink::test]
+        fn zero_amounts_are_rejected() {
+            let mut contract = create_contract();
+            let alice = default_accounts().alice;
+
+            assert_eq!(
+                contract.deposit_for(alice, 0),
+                Err(WrapperError::ZeroAmount)
+            );
+            assert_eq!(
+                contract.withdraw_to(alice, 0),
+                Err(WrapperError::ZeroAmount)
+            );
+        }
+
+        #[ink::test]
+        fn wrapped_balances_carry_votes() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(contract.delegate(accounts.bob), Ok(()));
+            assert_eq!(
+                psp22::Internal::_mint_to(&mut contract, accounts.alice, 40),
+                Ok(())
+            );
+            assert_eq!(
+                psp22::Internal::_mint_to(&mut contract, accounts.charlie, 60),
+                Ok(())
+            );
+
+            assert_eq!(contract.get_votes(accounts.bob), 40);
+            assert_eq!(contract.weight(accounts.alice), 4_000);
+
+            ink::env::test::set_block_timestamp::<Environment>(10);
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, accounts.charlie, 10, Vec::new()),
+                Ok(())
+            );
+            assert_eq!(contract.get_votes(accounts.bob), 30);
+            assert_eq!(contract.get_past_votes(accounts.bob, 5), 40);
+        }
+
+        #[ink::test]
+        fn balances_and_supply_are_checkpointed() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+
+            ink::env::test::set_block_timestamp::<Environment>(10);
+            assert_eq!(
+                psp22::Internal::_mint_to(&mut contract, accounts.alice, 40),
+                Ok(())
+            );
+
+            ink::env::test::set_block_timestamp::<Environment>(20);
+            assert_eq!(
+                psp22::PSP22::transfer(&mut contract, accounts.bob, 15, Vec::new()),
+                Ok(())
+            );
+            assert_eq!(
+                psp22::Internal::_burn_from(&mut contract, accounts.alice, 5),
+                Ok(())
+            );
+
+            assert_eq!(contract.balance_of_at(accounts.alice, 5), 0);
+            assert_eq!(contract.balance_of_at(accounts.alice, 15), 40);
+            assert_eq!(contract.balance_of_at(accounts.alice, 20), 20);
+            assert_eq!(contract.balance_of_at(accounts.bob, 15), 0);
+            assert_eq!(contract.balance_of_at(accounts.bob, 25), 15);
+
+            assert_eq!(contract.total_supply_at(5), 0);
+            assert_eq!(contract.total_supply_at(15), 40);
+            assert_eq!(contract.total_supply_at(20), 35);
+            assert_eq!(contract.circulating_supply_at(20), 35);
+        }
+    }
+}
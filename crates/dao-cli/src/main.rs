@@ -0,0 +1,188 @@
+//! Command line access to a Governor, for scripts and CI.
+//!
+//! The node, the Governor and the signing key come from a TOML config file:
+//!
+//! ```toml
+//! url = "ws://127.0.0.1:9944"
+//! governor = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! suri = "//Alice"
+//! ```
+
+use std::{
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{
+    Parser,
+    Subcommand,
+    ValueEnum,
+};
+use dao_client::{
+    Balance,
+    DaoClient,
+    ProposalId,
+    VoteType,
+};
+use serde::Deserialize;
+use subxt::{
+    ext::sp_core::{
+        sr25519,
+        Pair,
+    },
+    tx::PairSigner,
+    utils::AccountId32,
+    PolkadotConfig,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(about = "Propose, vote and execute on an investment DAO")]
+struct Cli {
+    /// Config file with the node `url`, the `governor` address and the `suri` of
+    /// the signing key.
+    #[arg(long, default_value = "dao-cli.toml")]
+    config: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Proposes to transfer `amount` to `to`.
+    Propose {
+        to: AccountId32,
+        amount: Balance,
+        /// Voting period, in minutes.
+        duration: u64,
+        /// Proposal deposit, if the Governor requires one.
+        #[arg(long, default_value_t = 0)]
+        deposit: Balance,
+    },
+    Vote {
+        proposal_id: ProposalId,
+        vote: Vote,
+    },
+    Execute {
+        proposal_id: ProposalId,
+    },
+    /// Shows a proposal and whether it can be executed.
+    Status {
+        proposal_id: ProposalId,
+    },
+    Tally {
+        proposal_id: ProposalId,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Vote {
+    Against,
+    For,
+    Abstain,
+}
+
+impl From<Vote> for VoteType {
+    fn from(vote: Vote) -> Self {
+        match vote {
+            Vote::Against => VoteType::Against,
+            Vote::For => VoteType::For,
+            Vote::Abstain => VoteType::Abstain,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Config {
+    url: String,
+    governor: AccountId32,
+    /// Secret URI of the signing key, e.g. a mnemonic or `//Alice`.
+    suri: String,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let config: Config = toml::from_str(&std::fs::read_to_string(&cli.config)?)?;
+    let client = DaoClient::new(&config.url, config.governor).await?;
+    let pair = sr25519::Pair::from_string(&config.suri, None)
+        .map_err(|error| format!("invalid suri: {error:?}"))?;
+    let signer = PairSigner::<PolkadotConfig, _>::new(pair);
+
+    match cli.command {
+        Command::Propose {
+            to,
+            amount,
+            duration,
+            deposit,
+        } => {
+            client
+                .propose(&signer, to, amount, duration, deposit)
+                .await?;
+            println!("proposed");
+        }
+        Command::Vote { proposal_id, vote } => {
+            client.vote(&signer, proposal_id, vote.into()).await?;
+            println!("voted on proposal {proposal_id}");
+        }
+        Command::Execute { proposal_id } => {
+            client.execute(&signer, proposal_id).await?;
+            println!("executed proposal {proposal_id}");
+        }
+        Command::Status { proposal_id } => {
+            let (proposal, body) = match (
+                client.get_proposal(proposal_id).await?,
+                client.get_proposal_body(proposal_id).await?,
+            ) {
+                (Some(proposal), Some(body)) => (proposal, body),
+                _ => return Err(format!("proposal {proposal_id} not found").into()),
+            };
+
+            println!("proposer: {}", account(proposal.proposer));
+            println!("to:       {}", account(body.to));
+            println!("amount:   {}", body.amount);
+            if let Some(asset) = body.asset {
+                println!("asset:    {}", account(asset));
+            }
+            println!(
+                "voting:   {} - {}",
+                proposal.vote_start,
+                proposal.vote_end()
+            );
+            if client.is_executed(proposal_id).await? {
+                println!("status:   executed");
+            } else {
+                match client.can_execute(proposal_id).await {
+                    Ok(()) => println!("status:   executable"),
+                    Err(error) => println!("status:   not executable, {error}"),
+                }
+            }
+        }
+        Command::Tally { proposal_id } => {
+            let tally = client.tally(proposal_id).await?;
+            println!("for:      {}", tally.for_votes);
+            println!("against:  {}", tally.against_vote);
+            println!("abstain:  {}", tally.abstain_votes);
+            println!("quorum:   {}%", tally.quorum_progress);
+            println!("passing:  {}", tally.passing);
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts an account of the Governor's types, to print it as an SS58 address.
+fn account(account: impl AsRef<[u8; 32]>) -> AccountId32 {
+    AccountId32(*account.as_ref())
+}
@@ -0,0 +1,348 @@
+//! Typed access to a deployed Governor over a node's RPC, so that bots and backends
+//! can propose, vote, execute and query without encoding calls by hand.
+//!
+//! Queries are dry runs through the `ContractsApi_call` runtime API. Transactions
+//! are dry-run first, so that a call the Governor rejects returns its [`DaoError`]
+//! without being submitted, and then sent as a `Contracts::call` extrinsic with the
+//! weight the dry run required.
+
+use core::fmt;
+
+pub use dao::{
+    DaoError,
+    ExternalError,
+    Invariant,
+    Proposal,
+    ProposalBody,
+    ProposalId,
+    ProposalOutcome,
+    Tally,
+    VoteType,
+};
+use pallet_contracts_primitives::ContractExecResult;
+use scale::{
+    Decode,
+    Encode,
+};
+use subxt::{
+    ext::scale_encode::EncodeAsType,
+    tx::Signer,
+    utils::{
+        AccountId32,
+        MultiAddress,
+    },
+    OnlineClient,
+    PolkadotConfig,
+};
+
+pub type Balance = u128;
+
+#[derive(Debug)]
+pub enum Error {
+    Rpc(subxt::Error),
+    Decode(scale::Error),
+    /// The Governor trapped or reverted, e.g. on an unknown selector.
+    Call(String),
+    /// The Governor rejected the call.
+    Dao(DaoError),
+}
+
+impl From<subxt::Error> for Error {
+    fn from(error: subxt::Error) -> Self {
+        Error::Rpc(error)
+    }
+}
+
+impl From<scale::Error> for Error {
+    fn from(error: scale::Error) -> Self {
+        Error::Decode(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Rpc(error) => write!(f, "rpc error: {error}"),
+            Error::Decode(error) => write!(f, "cannot decode the result: {error}"),
+            Error::Call(reason) => write!(f, "call failed: {reason}"),
+            Error::Dao(error) => {
+                write!(
+                    f,
+                    "rejected by the Governor: {error:?} (code {})",
+                    error.error_code()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Weight in the layout of `sp_weights::Weight`.
+#[derive(Clone, Copy, Debug, Encode, Decode, EncodeAsType)]
+#[encode_as_type(crate_path = "subxt::ext::scale_encode")]
+struct Weight {
+    #[codec(compact)]
+    ref_time: u64,
+    #[codec(compact)]
+    proof_size: u64,
+}
+
+/// Arguments of the `ContractsApi_call` runtime API.
+#[derive(Encode)]
+struct CallRequest {
+    origin: AccountId32,
+    dest: AccountId32,
+    value: Balance,
+    gas_limit: Option<Weight>,
+    storage_deposit_limit: Option<Balance>,
+    input_data: Vec<u8>,
+}
+
+/// Arguments of the `Contracts::call` extrinsic.
+#[derive(EncodeAsType)]
+#[encode_as_type(crate_path = "subxt::ext::scale_encode")]
+struct Call {
+    dest: MultiAddress<AccountId32, ()>,
+    value: Balance,
+    gas_limit: Weight,
+    storage_deposit_limit: Option<Balance>,
+    data: Vec<u8>,
+}
+
+/// Client of one Governor deployment.
+pub struct DaoClient {
+    api: OnlineClient<PolkadotConfig>,
+    governor: AccountId32,
+}
+
+impl DaoClient {
+    /// Connects to the node at `url`, e.g. `ws://127.0.0.1:9944`.
+    pub async fn new(url: &str, governor: AccountId32) -> Result<Self, Error> {
+        Ok(Self {
+            api: OnlineClient::from_url(url).await?,
+            governor,
+        })
+    }
+
+    pub fn governor(&self) -> &AccountId32 {
+        &self.governor
+    }
+
+    /// Returns the connection to the node, e.g. to subscribe to blocks.
+    pub fn api(&self) -> &OnlineClient<PolkadotConfig> {
+        &self.api
+    }
+
+    /// Proposes to transfer `amount` to `to`, voting for `duration` minutes.
+    /// `deposit` has to match the Governor's `proposal_deposit`.
+    pub async fn propose<S>(
+        &self,
+        signer: &S,
+        to: AccountId32,
+        amount: Balance,
+        duration: u64,
+        deposit: Balance,
+    ) -> Result<(), Error>
+    where
+        S: Signer<PolkadotConfig> + Send + Sync,
+    {
+        let input = encode_call(ink::selector_bytes!("propose"), (to, amount, duration));
+        self.submit(signer, input, deposit).await
+    }
+
+    pub async fn vote<S>(
+        &self,
+        signer: &S,
+        proposal_id: ProposalId,
+        vote: VoteType,
+    ) -> Result<(), Error>
+    where
+        S: Signer<PolkadotConfig> + Send + Sync,
+    {
+        let input = encode_call(ink::selector_bytes!("vote"), (proposal_id, vote));
+        self.submit(signer, input, 0).await
+    }
+
+    pub async fn execute<S>(
+        &self,
+        signer: &S,
+        proposal_id: ProposalId,
+    ) -> Result<(), Error>
+    where
+        S: Signer<PolkadotConfig> + Send + Sync,
+    {
+        let input = encode_call(ink::selector_bytes!("execute"), proposal_id);
+        self.submit(signer, input, 0).await
+    }
+
+    pub async fn get_proposal(
+        &self,
+        proposal_id: ProposalId,
+    ) -> Result<Option<Proposal>, Error> {
+        self.query(encode_call(
+            ink::selector_bytes!("get_proposal"),
+            proposal_id,
+        ))
+        .await
+    }
+
+    pub async fn get_proposal_body(
+        &self,
+        proposal_id: ProposalId,
+    ) -> Result<Option<ProposalBody>, Error> {
+        self.query(encode_call(
+            ink::selector_bytes!("get_proposal_body"),
+            proposal_id,
+        ))
+        .await
+    }
+
+    pub async fn tally(&self, proposal_id: ProposalId) -> Result<Tally, Error> {
+        let tally: Result<Tally, DaoError> = self
+            .query(encode_call(ink::selector_bytes!("tally"), proposal_id))
+            .await?;
+        tally.map_err(Error::Dao)
+    }
+
+    pub async fn is_executed(&self, proposal_id: ProposalId) -> Result<bool, Error> {
+        self.query(encode_call(
+            ink::selector_bytes!("is_executed"),
+            proposal_id,
+        ))
+        .await
+    }
+
+    /// Returns why a proposal cannot be executed yet, if it cannot.
+    pub async fn can_execute(&self, proposal_id: ProposalId) -> Result<(), Error> {
+        let result: Result<(), DaoError> = self
+            .query(encode_call(
+                ink::selector_bytes!("can_execute"),
+                proposal_id,
+            ))
+            .await?;
+        result.map_err(Error::Dao)
+    }
+
+    /// Dry-runs a message as the Governor itself and decodes its return value.
+    async fn query<R: Decode>(&self, input: Vec<u8>) -> Result<R, Error> {
+        let (_, data) = self.dry_run(self.governor.clone(), input, 0).await?;
+        decode_message_result(&data)
+    }
+
+    /// Dry-runs a message returning `Result<(), DaoError>` and, if it succeeds,
+    /// submits it and waits for it to be finalized.
+    async fn submit<S>(
+        &self,
+        signer: &S,
+        input: Vec<u8>,
+        value: Balance,
+    ) -> Result<(), Error>
+    where
+        S: Signer<PolkadotConfig> + Send + Sync,
+    {
+        let (gas_limit, data) = self
+            .dry_run(signer.account_id().clone(), input.clone(), value)
+            .await?;
+        let result: Result<(), DaoError> = decode_message_result(&data)?;
+        result.map_err(Error::Dao)?;
+
+        let call = Call {
+            dest: MultiAddress::Id(self.governor.clone()),
+            value,
+            gas_limit,
+            storage_deposit_limit: None,
+            data: input,
+        };
+        let payload = subxt::tx::Payload::new("Contracts", "call", call).unvalidated();
+
+        self.api
+            .tx()
+            .sign_and_submit_then_watch_default(&payload, signer)
+            .await?
+            .wait_for_finalized_success()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the weight a call requires and its output.
+    async fn dry_run(
+        &self,
+        origin: AccountId32,
+        input: Vec<u8>,
+        value: Balance,
+    ) -> Result<(Weight, Vec<u8>), Error> {
+        let request = CallRequest {
+            origin,
+            dest: self.governor.clone(),
+            value,
+            gas_limit: None,
+            storage_deposit_limit: None,
+            input_data: input,
+        };
+        let bytes = self
+            .api
+            .rpc()
+            .state_call("ContractsApi_call", Some(&request.encode()), None)
+            .await?;
+
+        let result = ContractExecResult::<Balance>::decode(&mut bytes.as_ref())?;
+        let output = match result.result {
+            Ok(output) if !output.did_revert() => output,
+            Ok(_) => return Err(Error::Call("the Governor reverted".into())),
+            Err(error) => return Err(Error::Call(format!("{error:?}"))),
+        };
+
+        let weight = Weight {
+            ref_time: result.gas_required.ref_time(),
+            proof_size: result.gas_required.proof_size(),
+        };
+        Ok((weight, output.data))
+    }
+}
+
+/// Encodes a message call: its selector followed by its arguments.
+fn encode_call<Args: Encode>(selector: [u8; 4], args: Args) -> Vec<u8> {
+    let mut input = selector.to_vec();
+    args.encode_to(&mut input);
+    input
+}
+
+/// Decodes the output of an ink! message, which wraps the return value in a
+/// `Result` for errors of the language itself.
+fn decode_message_result<R: Decode>(data: &[u8]) -> Result<R, Error> {
+    match ink::MessageResult::<R>::decode(&mut &data[..])? {
+        Ok(value) => Ok(value),
+        Err(error) => Err(Error::Call(format!("{error:?}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_are_encoded_like_ink() {
+        let input = encode_call(ink::selector_bytes!("vote"), (7u64, VoteType::For));
+
+        assert_eq!(&input[..4], &ink::selector_bytes!("vote"));
+        assert_eq!(&input[4..], &[7, 0, 0, 0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn message_results_are_unwrapped() {
+        let ok: ink::MessageResult<Result<(), DaoError>> =
+            Ok(Err(DaoError::AlreadyVoted));
+        assert_eq!(
+            decode_message_result::<Result<(), DaoError>>(&ok.encode()).unwrap(),
+            Err(DaoError::AlreadyVoted)
+        );
+
+        let failed: ink::MessageResult<bool> = Err(ink::LangError::CouldNotReadInput);
+        assert!(matches!(
+            decode_message_result::<bool>(&failed.encode()),
+            Err(Error::Call(_))
+        ));
+    }
+}
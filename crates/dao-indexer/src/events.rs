@@ -0,0 +1,162 @@
+use dao_client::{
+    Balance,
+    Invariant,
+    ProposalId,
+    ProposalOutcome,
+    VoteType,
+};
+use scale::Decode;
+use subxt::{
+    ext::scale_decode::DecodeAsType,
+    utils::AccountId32,
+};
+
+/// `Contracts::ContractEmitted`, the runtime event carrying a contract event.
+#[derive(Debug, Decode, DecodeAsType)]
+#[decode_as_type(crate_path = "subxt::ext::scale_decode")]
+pub struct ContractEmitted {
+    pub contract: AccountId32,
+    pub data: Vec<u8>,
+}
+
+impl subxt::events::StaticEvent for ContractEmitted {
+    const PALLET: &'static str = "Contracts";
+    const EVENT: &'static str = "ContractEmitted";
+}
+
+/// Events of the Governor. ink! encodes an event as its index among all events of
+/// the contract, in declaration order, followed by all of its fields, so the
+/// variants have to follow the order of the `#[ink(event)]` structs in
+/// `contracts/dao`.
+#[derive(Debug, Clone, PartialEq, Eq, Decode)]
+pub enum GovernorEvent {
+    ProposalCreated {
+        proposal_id: ProposalId,
+        proposer: AccountId32,
+        vote_start: u64,
+        vote_end: u64,
+    },
+    ProposalExecuted {
+        proposal_id: ProposalId,
+        proposer: AccountId32,
+        success: bool,
+        output_hash: [u8; 32],
+        error_code: Option<u16>,
+    },
+    ProposalFinalized {
+        proposal_id: ProposalId,
+        for_votes: Balance,
+        against_vote: Balance,
+        abstain_votes: Balance,
+        outcome: ProposalOutcome,
+    },
+    VoteCast {
+        proposal_id: ProposalId,
+        voter: AccountId32,
+        vote: VoteType,
+        weight: Balance,
+    },
+    CouncilVoteCast {
+        proposal_id: ProposalId,
+        member: AccountId32,
+        vote: VoteType,
+    },
+    EmergencyCosigned {
+        proposal_id: ProposalId,
+        guardian: AccountId32,
+    },
+    InvariantViolated {
+        invariant: Invariant,
+        proposal_id: Option<ProposalId>,
+    },
+    TreasurerPayout {
+        treasurer: AccountId32,
+        to: AccountId32,
+        amount: Balance,
+    },
+    Deposited {
+        receipt_id: u64,
+        contributor: AccountId32,
+        asset: Option<AccountId32>,
+        amount: Balance,
+    },
+    MembershipRenewed {
+        member: AccountId32,
+        paid_until: u64,
+    },
+    SignalPassed {
+        signal_id: ProposalId,
+        binding_id: ProposalId,
+    },
+    ProposalVetoed {
+        proposal_id: ProposalId,
+        vetoed_by: AccountId32,
+        reason_hash: [u8; 32],
+    },
+    ShareClassIssued {
+        token: AccountId32,
+        dividend_weight: u32,
+    },
+    DissolutionStarted {
+        proposal_id: ProposalId,
+        supply: Balance,
+    },
+    Liquidated {
+        asset: AccountId32,
+        venue: AccountId32,
+        amount: Balance,
+        proceeds: Balance,
+    },
+    DissolutionClaimed {
+        holder: AccountId32,
+        redeemed: Balance,
+        native: Balance,
+    },
+    RedemptionRequested {
+        request_id: u64,
+        holder: AccountId32,
+        amount: Balance,
+        epoch: u64,
+    },
+    EpochSettled {
+        epoch: u64,
+        queued: Balance,
+        filled: Balance,
+        paid: Balance,
+    },
+    RedemptionWithdrawn {
+        request_id: u64,
+        holder: AccountId32,
+        filled: Balance,
+        paid: Balance,
+    },
+    LoanIssued {
+        loan_id: u64,
+        borrower: AccountId32,
+        principal: Balance,
+    },
+    LoanRepaid {
+        loan_id: u64,
+        amount: Balance,
+        outstanding: Balance,
+    },
+    LoanDefaulted {
+        loan_id: u64,
+        borrower: AccountId32,
+        outstanding: Balance,
+    },
+    CollateralSeized {
+        loan_id: u64,
+        asset: Option<AccountId32>,
+        amount: Balance,
+    },
+    CouncilElected {
+        term: u64,
+        members: Vec<AccountId32>,
+    },
+    RedemptionCancelled {
+        request_id: u64,
+        holder: AccountId32,
+        refunded: Balance,
+    },
+}
@@ -0,0 +1,257 @@
+//! Follows the events of a Governor into a local sled database of proposals, votes
+//! and treasury outflows, as the backend of dashboards.
+//!
+//! [`Indexer::run`] subscribes to finalized blocks and applies every event the
+//! Governor emits to the [`Store`], which answers the queries.
+
+mod events;
+mod store;
+
+use core::fmt;
+
+use dao_client::DaoClient;
+
+use self::events::ContractEmitted;
+pub use self::{
+    events::GovernorEvent,
+    store::{
+        ProposalRecord,
+        Store,
+        TreasuryFlow,
+        VoteRecord,
+    },
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Rpc(subxt::Error),
+    Client(dao_client::Error),
+    Store(sled::Error),
+    Decode(scale::Error),
+}
+
+impl From<subxt::Error> for Error {
+    fn from(error: subxt::Error) -> Self {
+        Error::Rpc(error)
+    }
+}
+
+impl From<dao_client::Error> for Error {
+    fn from(error: dao_client::Error) -> Self {
+        Error::Client(error)
+    }
+}
+
+impl From<sled::Error> for Error {
+    fn from(error: sled::Error) -> Self {
+        Error::Store(error)
+    }
+}
+
+impl From<scale::Error> for Error {
+    fn from(error: scale::Error) -> Self {
+        Error::Decode(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Rpc(error) => write!(f, "rpc error: {error}"),
+            Error::Client(error) => write!(f, "{error}"),
+            Error::Store(error) => write!(f, "store error: {error}"),
+            Error::Decode(error) => write!(f, "cannot decode: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub struct Indexer {
+    client: DaoClient,
+    store: Store,
+}
+
+impl Indexer {
+    pub fn new(client: DaoClient, store: Store) -> Self {
+        Self { client, store }
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Applies the Governor's events of every finalized block from now on, until
+    /// the subscription ends or fails.
+    pub async fn run(&self) -> Result<(), Error> {
+        let mut blocks = self.client.api().blocks().subscribe_finalized().await?;
+
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let number = block.header().number;
+
+            for emitted in block.events().await?.find::<ContractEmitted>() {
+                let emitted = emitted?;
+                if &emitted.contract != self.client.governor() {
+                    continue
+                }
+
+                let event = GovernorEvent::decode(&mut &emitted.data[..])?;
+                self.index(number, &event).await?;
+            }
+
+            self.store.set_last_block(number)?;
+        }
+
+        Ok(())
+    }
+
+    async fn index(&self, block: u32, event: &GovernorEvent) -> Result<(), Error> {
+        // The event lacks the body, so it is queried once
+        if let GovernorEvent::ProposalCreated {
+            proposal_id,
+            proposer,
+            vote_start,
+            vote_end,
+        } = event
+        {
+            if let Some(body) = self.client.get_proposal_body(*proposal_id).await? {
+                self.store.insert_proposal(&ProposalRecord {
+                    proposal_id: *proposal_id,
+                    proposer: proposer.clone(),
+                    to: subxt::utils::AccountId32(*body.to.as_ref()),
+                    amount: body.amount,
+                    asset: body
+                        .asset
+                        .map(|asset| subxt::utils::AccountId32(*asset.as_ref())),
+                    vote_start: *vote_start,
+                    vote_end: *vote_end,
+                    execution: None,
+                    outcome: None,
+                })?;
+            }
+        }
+
+        self.store.apply(block, event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dao_client::{
+        ProposalOutcome,
+        VoteType,
+    };
+    use scale::Encode;
+    use subxt::utils::AccountId32;
+
+    fn proposal(proposal_id: u64) -> ProposalRecord {
+        ProposalRecord {
+            proposal_id,
+            proposer: AccountId32([1; 32]),
+            to: AccountId32([5; 32]),
+            amount: 100,
+            asset: None,
+            vote_start: 0,
+            vote_end: 60,
+            execution: None,
+            outcome: None,
+        }
+    }
+
+    #[test]
+    fn events_decode_by_declaration_order() {
+        // `VoteCast` is the fourth event of the Governor
+        let mut data = vec![3];
+        (7u64, [2u8; 32], VoteType::For, 50u128).encode_to(&mut data);
+
+        assert_eq!(
+            GovernorEvent::decode(&mut &data[..]).unwrap(),
+            GovernorEvent::VoteCast {
+                proposal_id: 7,
+                voter: AccountId32([2; 32]),
+                vote: VoteType::For,
+                weight: 50,
+            }
+        );
+    }
+
+    #[test]
+    fn store_tracks_votes_and_outflows() {
+        let store = Store::temporary().unwrap();
+        store.insert_proposal(&proposal(1)).unwrap();
+
+        let vote = GovernorEvent::VoteCast {
+            proposal_id: 1,
+            voter: AccountId32([2; 32]),
+            vote: VoteType::For,
+            weight: 50,
+        };
+        store.apply(10, &vote).unwrap();
+        store
+            .apply(
+                12,
+                &GovernorEvent::ProposalExecuted {
+                    proposal_id: 1,
+                    proposer: AccountId32([1; 32]),
+                    success: true,
+                    output_hash: [0; 32],
+                    error_code: None,
+                },
+            )
+            .unwrap();
+        store
+            .apply(
+                12,
+                &GovernorEvent::ProposalFinalized {
+                    proposal_id: 1,
+                    for_votes: 50,
+                    against_vote: 0,
+                    abstain_votes: 0,
+                    outcome: ProposalOutcome::Executed,
+                },
+            )
+            .unwrap();
+        store
+            .apply(
+                13,
+                &GovernorEvent::TreasurerPayout {
+                    treasurer: AccountId32([3; 32]),
+                    to: AccountId32([4; 32]),
+                    amount: 25,
+                },
+            )
+            .unwrap();
+
+        let record = store.proposal(1).unwrap().unwrap();
+        assert_eq!(record.execution, Some(true));
+        assert_eq!(record.outcome, Some(ProposalOutcome::Executed));
+        assert_eq!(store.votes(1).unwrap().len(), 1);
+        assert_eq!(store.votes(2).unwrap().len(), 0);
+        assert_eq!(store.treasury_flows().unwrap().len(), 2);
+        assert_eq!(store.total_outflow().unwrap(), 125);
+    }
+
+    #[test]
+    fn failed_calls_move_no_funds() {
+        let store = Store::temporary().unwrap();
+        store.insert_proposal(&proposal(1)).unwrap();
+
+        store
+            .apply(
+                12,
+                &GovernorEvent::ProposalExecuted {
+                    proposal_id: 1,
+                    proposer: AccountId32([1; 32]),
+                    success: false,
+                    output_hash: [0; 32],
+                    error_code: Some(700),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(store.proposal(1).unwrap().unwrap().execution, Some(false));
+        assert_eq!(store.total_outflow().unwrap(), 0);
+    }
+}
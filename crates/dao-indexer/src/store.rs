@@ -0,0 +1,255 @@
+use std::path::Path;
+
+use dao_client::{
+    Balance,
+    ProposalId,
+    ProposalOutcome,
+    VoteType,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+use subxt::utils::AccountId32;
+
+use crate::{
+    events::GovernorEvent,
+    Error,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ProposalRecord {
+    pub proposal_id: ProposalId,
+    pub proposer: AccountId32,
+    /// Recipient of the transfer. Calls and payout batches are sent to this
+    /// account too, batches to the Governor itself.
+    pub to: AccountId32,
+    pub amount: Balance,
+    /// PSP22 token the proposal pays in, `None` for native tokens.
+    pub asset: Option<AccountId32>,
+    pub vote_start: u64,
+    pub vote_end: u64,
+    /// Whether the execution succeeded, once executed.
+    pub execution: Option<bool>,
+    pub outcome: Option<ProposalOutcome>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct VoteRecord {
+    pub voter: AccountId32,
+    pub vote: VoteType,
+    pub weight: Balance,
+}
+
+/// Funds leaving the treasury.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct TreasuryFlow {
+    pub block: u32,
+    /// Executed proposal, or `None` for a treasurer payout.
+    pub proposal_id: Option<ProposalId>,
+    pub to: AccountId32,
+    pub amount: Balance,
+    /// PSP22 token that left, `None` for native tokens.
+    pub asset: Option<AccountId32>,
+}
+
+/// Proposals, votes and treasury flows of one Governor, kept in a sled database.
+pub struct Store {
+    proposals: sled::Tree,
+    votes: sled::Tree,
+    flows: sled::Tree,
+    meta: sled::Tree,
+}
+
+const LAST_BLOCK: &[u8] = b"last_block";
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_db(sled::open(path)?)
+    }
+
+    /// Opens a store that is removed when dropped.
+    pub fn temporary() -> Result<Self, Error> {
+        Self::from_db(sled::Config::new().temporary(true).open()?)
+    }
+
+    fn from_db(db: sled::Db) -> Result<Self, Error> {
+        Ok(Self {
+            proposals: db.open_tree("proposals")?,
+            votes: db.open_tree("votes")?,
+            flows: db.open_tree("flows")?,
+            meta: db.open_tree("meta")?,
+        })
+    }
+
+    /// Returns the last block whose events were applied.
+    pub fn last_block(&self) -> Result<Option<u32>, Error> {
+        self.meta
+            .get(LAST_BLOCK)?
+            .map(|value| decode(&value))
+            .transpose()
+    }
+
+    pub fn set_last_block(&self, block: u32) -> Result<(), Error> {
+        self.meta.insert(LAST_BLOCK, block.encode())?;
+        Ok(())
+    }
+
+    /// Records a new proposal, with the body queried from the Governor.
+    pub fn insert_proposal(&self, record: &ProposalRecord) -> Result<(), Error> {
+        self.proposals
+            .insert(record.proposal_id.to_be_bytes(), record.encode())?;
+        Ok(())
+    }
+
+    /// Applies an event emitted in `block`. Events of proposals the store does not
+    /// know, i.e. created before indexing started, only update the votes.
+    pub fn apply(&self, block: u32, event: &GovernorEvent) -> Result<(), Error> {
+        match event {
+            GovernorEvent::ProposalExecuted {
+                proposal_id,
+                success,
+                ..
+            } => {
+                if let Some(mut record) = self.proposal(*proposal_id)? {
+                    record.execution = Some(*success);
+                    self.insert_proposal(&record)?;
+                    // A failed call leaves its transferred value in the treasury
+                    if *success {
+                        self.push_flow(&TreasuryFlow {
+                            block,
+                            proposal_id: Some(*proposal_id),
+                            to: record.to,
+                            amount: record.amount,
+                            asset: record.asset,
+                        })?;
+                    }
+                }
+            }
+            GovernorEvent::ProposalFinalized {
+                proposal_id,
+                outcome,
+                ..
+            } => {
+                if let Some(mut record) = self.proposal(*proposal_id)? {
+                    record.outcome = Some(*outcome);
+                    self.insert_proposal(&record)?;
+                }
+            }
+            GovernorEvent::VoteCast {
+                proposal_id,
+                voter,
+                vote,
+                weight,
+            } => {
+                let mut key = proposal_id.to_be_bytes().to_vec();
+                key.extend_from_slice(&voter.0);
+                let record = VoteRecord {
+                    voter: voter.clone(),
+                    vote: *vote,
+                    weight: *weight,
+                };
+                self.votes.insert(key, record.encode())?;
+            }
+            GovernorEvent::TreasurerPayout { to, amount, .. } => {
+                self.push_flow(&TreasuryFlow {
+                    block,
+                    proposal_id: None,
+                    to: to.clone(),
+                    amount: *amount,
+                    asset: None,
+                })?;
+            }
+            GovernorEvent::DissolutionClaimed { holder, native, .. } => {
+                self.push_flow(&TreasuryFlow {
+                    block,
+                    proposal_id: None,
+                    to: holder.clone(),
+                    amount: *native,
+                    asset: None,
+                })?;
+            }
+            GovernorEvent::RedemptionWithdrawn { holder, paid, .. } => {
+                self.push_flow(&TreasuryFlow {
+                    block,
+                    proposal_id: None,
+                    to: holder.clone(),
+                    amount: *paid,
+                    asset: None,
+                })?;
+            }
+            GovernorEvent::LoanIssued {
+                borrower,
+                principal,
+                ..
+            } => {
+                self.push_flow(&TreasuryFlow {
+                    block,
+                    proposal_id: None,
+                    to: borrower.clone(),
+                    amount: *principal,
+                    asset: None,
+                })?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn proposal(
+        &self,
+        proposal_id: ProposalId,
+    ) -> Result<Option<ProposalRecord>, Error> {
+        self.proposals
+            .get(proposal_id.to_be_bytes())?
+            .map(|value| decode(&value))
+            .transpose()
+    }
+
+    /// Returns all proposals, by id.
+    pub fn proposals(&self) -> Result<Vec<ProposalRecord>, Error> {
+        self.proposals
+            .iter()
+            .values()
+            .map(|value| decode(&value?))
+            .collect()
+    }
+
+    pub fn votes(&self, proposal_id: ProposalId) -> Result<Vec<VoteRecord>, Error> {
+        self.votes
+            .scan_prefix(proposal_id.to_be_bytes())
+            .values()
+            .map(|value| decode(&value?))
+            .collect()
+    }
+
+    /// Returns all treasury outflows, oldest first.
+    pub fn treasury_flows(&self) -> Result<Vec<TreasuryFlow>, Error> {
+        self.flows
+            .iter()
+            .values()
+            .map(|value| decode(&value?))
+            .collect()
+    }
+
+    /// Returns the native tokens that left the treasury.
+    pub fn total_outflow(&self) -> Result<Balance, Error> {
+        Ok(self
+            .treasury_flows()?
+            .iter()
+            .filter(|flow| flow.asset.is_none())
+            .map(|flow| flow.amount)
+            .sum())
+    }
+
+    fn push_flow(&self, flow: &TreasuryFlow) -> Result<(), Error> {
+        let index = self.flows.len() as u64;
+        self.flows.insert(index.to_be_bytes(), flow.encode())?;
+        Ok(())
+    }
+}
+
+fn decode<T: Decode>(value: &[u8]) -> Result<T, Error> {
+    Ok(T::decode(&mut &value[..])?)
+}
@@ -0,0 +1,281 @@
+//! Build tasks of the workspace, run with `cargo xtask <task>`.
+//!
+//! `bundle` builds every contract with `cargo contract`, validates the metadata,
+//! checks that the messages the Governor calls by selector still exist with that
+//! selector, and copies the artifacts into `target/bundle/<version>`.
+
+use std::{
+    error::Error,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process::Command,
+};
+
+use blake2::{
+    digest::consts::U32,
+    Blake2b,
+    Digest,
+};
+use serde_json::{
+    json,
+    Value,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Contract directories under `contracts/`, the Governor first.
+const CONTRACTS: [&str; 6] = [
+    "dao",
+    "governance-token",
+    "wrapped-token",
+    "snapshot-registry",
+    "deal-pipeline",
+    "share-class",
+];
+
+/// Messages the Governor calls through `ink::selector_bytes!`, by callee.
+const GOVERNOR_CALLS: [(&str, &[&str]); 4] = [
+    // External Governors of meta-governance proposals
+    ("dao", &["vote", "governance_token"]),
+    (
+        "governance-token",
+        &[
+            "delegates",
+            "get_past_votes",
+            "balance_of_at",
+            "circulating_supply_at",
+            "PSP22::balance_of",
+            "PSP22::total_supply",
+            // Redemptions filled by the exit queue
+            "burn",
+            // Treasury deposits, of any PSP22 token
+            "PSP22::transfer_from",
+            // Liquidations and claims of a dissolved DAO
+            "PSP22::transfer",
+            "PSP22::approve",
+        ],
+    ),
+    // Voting assets, and the governance token when it wraps an existing token
+    (
+        "wrapped-token",
+        &[
+            "delegates",
+            "get_past_votes",
+            "balance_of_at",
+            "circulating_supply_at",
+            "total_supply_at",
+            "PSP22::balance_of",
+            "PSP22::total_supply",
+        ],
+    ),
+    ("snapshot-registry", &["register", "verify", "total_weight"]),
+];
+
+fn main() {
+    let task = std::env::args().nth(1);
+    let result = match task.as_deref() {
+        Some("bundle") => bundle(),
+        _ => {
+            eprintln!("usage: cargo xtask bundle");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(error) = result {
+        eprintln!("error: {error}");
+        std::process::exit(1);
+    }
+}
+
+fn bundle() -> Result<()> {
+    let root = workspace_root();
+
+    for contract in CONTRACTS {
+        build(&root, contract)?;
+    }
+
+    let mut metadata = Vec::new();
+    for contract in CONTRACTS {
+        let value = read_metadata(&root, contract)?;
+        validate_metadata(contract, &value)?;
+        metadata.push((contract, value));
+    }
+
+    for (callee, labels) in GOVERNOR_CALLS {
+        let (_, value) = metadata
+            .iter()
+            .find(|(contract, _)| *contract == callee)
+            .ok_or_else(|| format!("{callee} is not built"))?;
+        check_selectors(callee, value, labels)?;
+    }
+
+    // The bundle is versioned after the Governor
+    let version = metadata[0].1["contract"]["version"]
+        .as_str()
+        .ok_or("dao metadata has no version")?
+        .to_owned();
+    let bundle = root.join("target").join("bundle").join(&version);
+    fs::create_dir_all(&bundle)?;
+
+    let mut manifest = Vec::new();
+    for (contract, value) in &metadata {
+        let artifact = artifact_path(&root, contract, "contract");
+        fs::copy(&artifact, bundle.join(artifact.file_name().unwrap()))?;
+        manifest.push(json!({
+            "name": value["contract"]["name"],
+            "version": value["contract"]["version"],
+            "code_hash": value["source"]["hash"],
+        }));
+    }
+    fs::write(
+        bundle.join("manifest.json"),
+        serde_json::to_string_pretty(
+            &json!({ "version": version, "contracts": manifest }),
+        )?,
+    )?;
+
+    println!(
+        "bundled {} contracts into {}",
+        CONTRACTS.len(),
+        bundle.display()
+    );
+    Ok(())
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask is inside the workspace")
+        .to_path_buf()
+}
+
+fn build(root: &Path, contract: &str) -> Result<()> {
+    let manifest = root.join("contracts").join(contract).join("Cargo.toml");
+    let status = Command::new("cargo")
+        .args(["contract", "build", "--release", "--manifest-path"])
+        .arg(&manifest)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("building {contract} failed").into())
+    }
+
+    Ok(())
+}
+
+/// Returns the path of an artifact written by `cargo contract` into the workspace
+/// target directory, which names it after the crate.
+fn artifact_path(root: &Path, contract: &str, extension: &str) -> PathBuf {
+    let name = contract.replace('-', "_");
+    root.join("target")
+        .join("ink")
+        .join(&name)
+        .join(format!("{name}.{extension}"))
+}
+
+fn read_metadata(root: &Path, contract: &str) -> Result<Value> {
+    let path = artifact_path(root, contract, "json");
+    let content = fs::read_to_string(&path)
+        .map_err(|error| format!("reading {}: {error}", path.display()))?;
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Checks that the metadata has a code hash, a version and constructors and
+/// messages with distinct selectors.
+fn validate_metadata(contract: &str, metadata: &Value) -> Result<()> {
+    if metadata["source"]["hash"].as_str().is_none() {
+        return Err(format!("{contract}: metadata has no code hash").into())
+    }
+
+    if metadata["contract"]["version"].as_str().is_none() {
+        return Err(format!("{contract}: metadata has no version").into())
+    }
+
+    for kind in ["constructors", "messages"] {
+        let entries = metadata["spec"][kind]
+            .as_array()
+            .filter(|entries| !entries.is_empty())
+            .ok_or_else(|| format!("{contract}: metadata has no {kind}"))?;
+
+        let mut selectors: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry["selector"].as_str().unwrap_or_default())
+            .collect();
+        selectors.sort_unstable();
+        if selectors.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(format!("{contract}: duplicate {kind} selectors").into())
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `callee` has a message for every label, with the selector that
+/// `ink::selector_bytes!` derives from the label.
+fn check_selectors(callee: &str, metadata: &Value, labels: &[&str]) -> Result<()> {
+    let messages = metadata["spec"]["messages"]
+        .as_array()
+        .ok_or_else(|| format!("{callee}: metadata has no messages"))?;
+
+    for label in labels {
+        let expected = selector(label);
+        let message = messages
+            .iter()
+            .find(|message| message["label"].as_str() == Some(*label))
+            .ok_or_else(|| format!("{callee} has no message {label}"))?;
+
+        match message["selector"].as_str() {
+            Some(actual) if actual == expected => {}
+            actual => {
+                return Err(format!(
+                    "{callee}::{label} has selector {}, the Governor calls {expected}",
+                    actual.unwrap_or("none"),
+                )
+                .into())
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the selector of a message label as `0x`-prefixed hex, the first four
+/// bytes of its BLAKE2b-256 hash.
+fn selector(label: &str) -> String {
+    let hash = Blake2b::<U32>::digest(label.as_bytes());
+    hash[..4]
+        .iter()
+        .fold(String::from("0x"), |hex, byte| hex + &format!("{byte:02x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selectors_match_ink() {
+        // The flipper example and the PSP22 standard
+        assert_eq!(selector("flip"), "0x633aa551");
+        assert_eq!(selector("PSP22::balance_of"), "0x6568382f");
+    }
+
+    #[test]
+    fn drifted_selectors_are_reported() {
+        let metadata = json!({
+            "spec": {
+                "messages": [
+                    { "label": "get_past_votes", "selector": selector("get_past_votes") },
+                    { "label": "delegates", "selector": "0x00000000" },
+                ],
+            },
+        });
+
+        assert!(check_selectors("token", &metadata, &["get_past_votes"]).is_ok());
+        assert!(check_selectors("token", &metadata, &["delegates"]).is_err());
+        assert!(check_selectors("token", &metadata, &["weight"]).is_err());
+    }
+}